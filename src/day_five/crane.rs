@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+use color_eyre::{Report, Result};
+
+use super::stacks::CrateStacks;
+
+/// Something that can carry out `move <count> from <from> to <to>`
+pub(super) trait Crane: Display {
+    fn apply(&self, stacks: &mut CrateStacks, count: usize, from: usize, to: usize) -> Result<()>;
+}
+
+/// Part one, moves crates one at a time so the block ends up upside down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn apply(&self, stacks: &mut CrateStacks, count: usize, from: usize, to: usize) -> Result<()> {
+        if from == to {
+            // Each crate is put straight back down, so the stack doesn't flip
+            for _ in 0..count {
+                let crates = stacks.take(from, 1)?;
+                stacks.put(to, crates)?;
+            }
+            return Ok(());
+        }
+        let mut crates = stacks.take(from, count)?;
+        crates.reverse();
+        stacks.put(to, crates)
+    }
+}
+
+impl Display for CrateMover9000 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CrateMover 9000")
+    }
+}
+
+/// Part two, moves the whole block at once so the order is kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn apply(&self, stacks: &mut CrateStacks, count: usize, from: usize, to: usize) -> Result<()> {
+        let crates = stacks.take(from, count)?;
+        stacks.put(to, crates)
+    }
+}
+
+impl Display for CrateMover9001 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CrateMover 9001")
+    }
+}
+
+/// Keeps the order like the 9001 but can only lift `capacity` crates at a time, so a big move
+/// is done as several smaller blocks starting from the top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ChunkedCrane {
+    pub(super) capacity: usize,
+}
+
+impl Crane for ChunkedCrane {
+    fn apply(&self, stacks: &mut CrateStacks, count: usize, from: usize, to: usize) -> Result<()> {
+        if self.capacity == 0 {
+            return Err(Report::msg("a crane with no capacity can't move anything"));
+        }
+        let mut left = count;
+        while left > 0 {
+            let chunk = left.min(self.capacity);
+            let crates = stacks.take(from, chunk)?;
+            stacks.put(to, crates)?;
+            left -= chunk;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ChunkedCrane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "chunked crane (capacity {})", self.capacity)
+    }
+}
+
+/// Lifts the whole block and spins it while carrying it, so the top `turns` crates of the block
+/// end up at the bottom when it's set down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct RotatingCrane {
+    pub(super) turns: usize,
+}
+
+impl Crane for RotatingCrane {
+    fn apply(&self, stacks: &mut CrateStacks, count: usize, from: usize, to: usize) -> Result<()> {
+        let mut crates = stacks.take(from, count)?;
+        if !crates.is_empty() {
+            let turns = self.turns % crates.len();
+            crates.rotate_right(turns);
+        }
+        stacks.put(to, crates)
+    }
+}
+
+impl Display for RotatingCrane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "rotating crane ({} turns)", self.turns)
+    }
+}
+
+/// Can only reach the stacks next to the one it's over, so a move is done as a chain of moves
+/// through every stack in between, each one made with `inner`
+pub(super) struct AdjacentCrane {
+    pub(super) inner: Box<dyn Crane>,
+}
+
+impl Crane for AdjacentCrane {
+    fn apply(&self, stacks: &mut CrateStacks, count: usize, from: usize, to: usize) -> Result<()> {
+        let from_index = stacks.index_of(from)?;
+        let to_index = stacks.index_of(to)?;
+        let path = if from_index <= to_index {
+            (from_index..=to_index).collect::<Vec<usize>>()
+        } else {
+            (to_index..=from_index).rev().collect()
+        };
+        for hop in path.windows(2) {
+            let (hop_from, hop_to) = (stacks.labels()[hop[0]], stacks.labels()[hop[1]]);
+            self.inner.apply(stacks, count, hop_from, hop_to)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for AdjacentCrane {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "adjacent only {}", self.inner)
+    }
+}
+
+/// Picks a crane from its name so it can be chosen at runtime:
+/// `9000`, `9001`, `chunked:<capacity>`, `rotating:<turns>` or `adjacent:<another crane>`
+pub(super) fn crane_by_name(name: &str) -> Result<Box<dyn Crane>> {
+    let name = name.trim();
+    let (model, setting) = match name.split_once(':') {
+        Some((model, setting)) => (model, Some(setting)),
+        None => (name, None),
+    };
+    let setting = |what: &str| {
+        setting.ok_or_else(|| Report::msg(format!("crane {:?} needs a {} after a :", model, what)))
+    };
+    Ok(match model {
+        "9000" => Box::new(CrateMover9000),
+        "9001" => Box::new(CrateMover9001),
+        "chunked" => Box::new(ChunkedCrane {
+            capacity: setting("capacity")?.parse()?,
+        }),
+        "rotating" => Box::new(RotatingCrane {
+            turns: setting("number of turns")?.parse()?,
+        }),
+        "adjacent" => Box::new(AdjacentCrane {
+            inner: crane_by_name(setting("crane to chain moves with")?)?,
+        }),
+        _ => return Err(Report::msg(format!("unknown crane {:?}", name))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> CrateStacks {
+        let lines =
+            ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "].map(str::to_string);
+        CrateStacks::parse_diagram(&lines, 1).unwrap()
+    }
+
+    fn after(crane: &str, count: usize, from: usize, to: usize) -> Vec<Vec<String>> {
+        let mut stacks = example();
+        crane_by_name(crane)
+            .unwrap()
+            .apply(&mut stacks, count, from, to)
+            .unwrap();
+        stacks.stacks().to_vec()
+    }
+
+    #[test]
+    fn test_cranes() {
+        assert_eq!(after("9000", 3, 2, 1)[0], vec!["Z", "N", "D", "C", "M"]);
+        assert_eq!(after("9001", 3, 2, 1)[0], vec!["Z", "N", "M", "C", "D"]);
+        assert_eq!(
+            after("chunked:2", 3, 2, 1)[0],
+            vec!["Z", "N", "C", "D", "M"]
+        );
+        assert_eq!(after("chunked:1", 3, 2, 1), after("9000", 3, 2, 1));
+        assert_eq!(after("chunked:5", 3, 2, 1), after("9001", 3, 2, 1));
+        assert_eq!(
+            after("rotating:1", 3, 2, 1)[0],
+            vec!["Z", "N", "D", "M", "C"]
+        );
+        assert_eq!(after("rotating:3", 3, 2, 1), after("9001", 3, 2, 1));
+
+        // 1 -> 2 -> 3 flips the block twice
+        let chained = after("adjacent:9000", 2, 1, 3);
+        assert_eq!(chained, after("9001", 2, 1, 3));
+        let chained = after("adjacent:9000", 1, 3, 1);
+        assert_eq!(chained[0], vec!["Z", "N", "P"]);
+        assert!(chained[1..]
+            .iter()
+            .all(|stack| !stack.contains(&"P".to_string())));
+    }
+
+    #[test]
+    fn test_same_stack() {
+        // Moving onto the stack it came from leaves it as it was, one crate at a time or not
+        for name in ["9000", "9001", "chunked:1", "chunked:2", "adjacent:9000"] {
+            assert_eq!(after(name, 2, 1, 1), example().stacks(), "{}", name);
+        }
+        // Lifting one crate again and again works even past the number on the stack
+        assert_eq!(after("9000", 3, 1, 1), after("chunked:1", 3, 1, 1));
+    }
+
+    #[test]
+    fn test_crane_errors() {
+        assert!(crane_by_name("9002").is_err());
+        assert!(crane_by_name("chunked").is_err());
+        assert!(crane_by_name("adjacent:chunked:x").is_err());
+        assert!(crane_by_name("chunked:0")
+            .unwrap()
+            .apply(&mut example(), 1, 1, 2)
+            .is_err());
+        assert!(crane_by_name("9000")
+            .unwrap()
+            .apply(&mut example(), 3, 3, 1)
+            .is_err());
+    }
+}
@@ -2,135 +2,124 @@
 use color_eyre::{Report, Result};
 use regex::Regex;
 
-fn parse_input_initial_state<I>(mut input_iterator: I) -> Result<(Vec<Vec<char>>, I)>
+use self::{
+    crane::{crane_by_name, Crane, CrateMover9000, CrateMover9001},
+    program::{read_moves, Program},
+    stacks::CrateStacks,
+};
+
+mod crane;
+mod planner;
+mod program;
+mod replay;
+mod stacks;
+mod validate;
+
+/// Reads the diagram up to the blank line, also giving back how many lines were used so the
+/// moves can be given line numbers
+fn read_initial_state<I>(input_iterator: &mut I) -> Result<(CrateStacks, usize)>
 where
     I: Iterator<Item = String>,
 {
     let mut graph_lines = Vec::new();
-    while let Some(line) = input_iterator.by_ref().next() {
+    let mut lines_used = 0;
+    for line in input_iterator.by_ref() {
+        lines_used += 1;
         let trimmed_line = line.trim_end_matches('\n');
-        if trimmed_line.is_empty() {
+        if trimmed_line.trim().is_empty() {
             break;
         } else {
             graph_lines.push(line);
         }
     }
 
-    // I'm just going to use the input I'm given as gospel for parsing
-    // using other things than spaces likes tabs is a problem for another day if ever
-
-    let mut final_vec = vec![
-        Vec::new();
-        (graph_lines
-            .get(0)
-            .ok_or_else(|| color_eyre::Report::msg("No lines found for the initial setup state"))?
-            .len()
-            + 1)
-            / 4
-    ]; // + 1 since there will be one less space for the separators
-
-    for line in graph_lines.into_iter().rev() {
-        // rev so the top crate is pushed last
-
-        for (final_index, index_in_line) in (1..line.len()).step_by(4).enumerate() {
-            let char_to_insert = line.chars().nth(index_in_line).ok_or_else(|| {
-                color_eyre::Report::msg(format!("expected_char in column {}", index_in_line / 4))
-            })?;
-            match &char_to_insert {
-                ' ' => (),
-                _ => final_vec.get_mut(final_index).unwrap().push(char_to_insert),
-            }
-        }
-    }
-    Ok((final_vec, input_iterator))
+    Ok((CrateStacks::parse_diagram(&graph_lines, 1)?, lines_used))
 }
 
-fn part_one_follow_the_rules<I>(input_iterator: I) -> Result<String>
+fn parse_input_initial_state<I>(mut input_iterator: I) -> Result<(CrateStacks, I)>
 where
     I: Iterator<Item = String>,
 {
-    let (mut graph, input_iterator) = parse_input_initial_state(input_iterator)?;
-    let move_regex = Regex::new("move ([^ ]*) from ([^ ]*) to ([^ ]*)")?;
-    for line in input_iterator {
-        let captures = move_regex.captures(&line).ok_or_else(|| {
-            Report::msg(format!("failed to find move locations in line->{:?}", line))
-        })?;
-        if captures.len() != 4 {
-            return Err(Report::msg(format!(
-                "unexpected number of captures for line->{:?}",
-                line
-            )));
-        }
-        for _ in 0..captures.get(1).unwrap().as_str().parse::<usize>()? {
-            let expected_index = captures.get(2).unwrap().as_str().parse::<usize>()? - 1;
-            let container = graph
-                .get_mut(expected_index)
-                .ok_or_else(|| Report::msg("no column found at expected index {expected_index}"))?
-                .pop()
-                .ok_or_else(|| Report::msg("No container found at expected location"))?;
-            graph
-                .get_mut(captures.get(3).unwrap().as_str().parse::<usize>()? - 1)
-                .ok_or_else(|| Report::msg("no column found at expected index {expected_index}"))?
-                .push(container);
-        }
-    }
+    let (graph, _) = read_initial_state(&mut input_iterator)?;
+    Ok((graph, input_iterator))
+}
 
-    let mut all_tops = "".to_string();
-    for top in graph.into_iter().filter_map(|mut column| column.pop()) {
-        all_tops += &top.to_string();
+/// Pulls `(count, from, to)` out of a move line. Only used by [`follow_the_rules_regex`] now,
+/// [`program::Move`] is a lot faster.
+fn parse_move_line(move_regex: &Regex, line: &str) -> Result<(usize, usize, usize)> {
+    let captures = move_regex
+        .captures(line)
+        .ok_or_else(|| Report::msg(format!("failed to find move locations in line->{:?}", line)))?;
+    if captures.len() != 4 {
+        return Err(Report::msg(format!(
+            "unexpected number of captures for line->{:?}",
+            line
+        )));
     }
-    Ok(all_tops)
+    Ok((
+        captures.get(1).unwrap().as_str().parse::<usize>()?,
+        captures.get(2).unwrap().as_str().parse::<usize>()?,
+        captures.get(3).unwrap().as_str().parse::<usize>()?,
+    ))
 }
 
-fn part_two_follow_the_rules<I>(input_iterator: I) -> Result<String>
+fn move_regex() -> Result<Regex> {
+    Ok(Regex::new("move ([^ ]*) from ([^ ]*) to ([^ ]*)")?)
+}
+
+/// Runs every move line after the diagram with `crane` and returns where the crates ended up
+fn follow_the_rules<I>(mut input_iterator: I, crane: &dyn Crane) -> Result<CrateStacks>
 where
     I: Iterator<Item = String>,
 {
-    let (mut graph, input_iterator) = parse_input_initial_state(input_iterator)?;
-    let move_regex = Regex::new("move ([^ ]*) from ([^ ]*) to ([^ ]*)")?;
-    for line in input_iterator {
-        let captures = move_regex.captures(&line).ok_or_else(|| {
-            Report::msg(format!("failed to find move locations in line->{:?}", line))
-        })?;
-        if captures.len() != 4 {
-            return Err(Report::msg(format!(
-                "unexpected number of captures for line->{:?}",
-                line
-            )));
-        }
-
-        let containers_to_grab = captures.get(1).unwrap().as_str().parse::<usize>()?;
-        let expected_index = captures.get(2).unwrap().as_str().parse::<usize>()? - 1;
-        let mut containers = Vec::with_capacity(containers_to_grab);
-        for _ in 0..containers_to_grab {
-            containers.push(
-                graph
-                    .get_mut(expected_index)
-                    .ok_or_else(|| {
-                        Report::msg("no column found at expected index {expected_index}")
-                    })?
-                    .pop()
-                    .ok_or_else(|| Report::msg("No container found at expected location"))?,
-            );
-        }
+    let (mut graph, lines_used) = read_initial_state(&mut input_iterator)?;
+    let moves = read_moves(input_iterator, lines_used + 1)?;
+    Program::compile(&graph, &moves, lines_used + 1)?.run(&mut graph, crane)?;
+    Ok(graph)
+}
 
-        for container in containers.into_iter().rev() {
-            graph
-                .get_mut(captures.get(3).unwrap().as_str().parse::<usize>()? - 1)
-                .ok_or_else(|| Report::msg("no column found at expected index {expected_index}"))?
-                .push(container);
-        }
+/// How [`follow_the_rules`] used to work, matching each line with a regex. Kept to benchmark
+/// against.
+fn follow_the_rules_regex<I>(mut input_iterator: I, crane: &dyn Crane) -> Result<CrateStacks>
+where
+    I: Iterator<Item = String>,
+{
+    let (mut graph, lines_used) = read_initial_state(&mut input_iterator)?;
+    let move_regex = move_regex()?;
+    for (line, line_number) in input_iterator.zip(lines_used + 1..) {
+        parse_move_line(&move_regex, &line)
+            .and_then(|(count, from, to)| crane.apply(&mut graph, count, from, to))
+            .map_err(|err| Report::msg(format!("line {}: {}", line_number, err)))?;
     }
+    Ok(graph)
+}
 
-    let mut all_tops = "".to_string();
-    for top in graph.into_iter().filter_map(|mut column| column.pop()) {
-        all_tops += &top.to_string();
-    }
-    Ok(all_tops)
+fn part_one_follow_the_rules<I>(input_iterator: I) -> Result<String>
+where
+    I: Iterator<Item = String>,
+{
+    Ok(follow_the_rules(input_iterator, &CrateMover9000)?.tops())
+}
+
+fn part_two_follow_the_rules<I>(input_iterator: I) -> Result<String>
+where
+    I: Iterator<Item = String>,
+{
+    Ok(follow_the_rules(input_iterator, &CrateMover9001)?.tops())
+}
+
+/// Same as the two parts but with any crane from [`crane_by_name`]
+fn follow_the_rules_with_crane<I>(input_iterator: I, crane_name: &str) -> Result<String>
+where
+    I: Iterator<Item = String>,
+{
+    Ok(follow_the_rules(input_iterator, crane_by_name(crane_name)?.as_ref())?.tops())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Instant;
+
     use crate::read_file_line_by_line;
 
     use super::*;
@@ -153,7 +142,11 @@ mod tests {
         ];
         let (graph, iterator) =
             parse_input_initial_state(input_lines.into_iter().map(str::to_string)).unwrap();
-        assert_eq!(graph, vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]);
+        assert_eq!(
+            graph.stacks(),
+            &[vec!["Z", "N"], vec!["M", "C", "D"], vec!["P"]]
+        );
+        assert_eq!(iterator.count(), 4);
     }
 
     #[test]
@@ -199,6 +192,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_part_one_same_stack() {
+        // Crates moved one at a time onto the stack they came from end up where they were
+        let input_lines = vec![
+            "    [D]    ",
+            "[N] [C]    ",
+            "[Z] [M] [P]",
+            " 1   2   3 ",
+            "",
+            "move 2 from 1 to 1",
+            "move 3 from 2 to 2",
+        ];
+        let result =
+            part_one_follow_the_rules(input_lines.into_iter().map(str::to_string)).unwrap();
+        assert_eq!(result, "NDP");
+    }
+
     #[test]
     fn test_part_two_example() {
         let input_lines = vec![
@@ -228,4 +238,68 @@ mod tests {
             Err(err) => panic!("{:?}", err),
         }
     }
+
+    #[test]
+    fn test_follow_the_rules_with_crane_my_input() {
+        for (crane, expected) in [
+            ("9000", "RTGWZTHLD"),
+            ("chunked:1", "RTGWZTHLD"),
+            ("9001", "STHGRZZFR"),
+            ("chunked:100", "STHGRZZFR"),
+            ("rotating:0", "STHGRZZFR"),
+        ] {
+            let lines = read_file_line_by_line("src/day_five/input.txt").unwrap();
+            assert_eq!(
+                follow_the_rules_with_crane(lines, crane).unwrap(),
+                expected,
+                "{}",
+                crane
+            );
+        }
+
+        let lines = read_file_line_by_line("src/day_five/input.txt").unwrap();
+        let adjacent = follow_the_rules_with_crane(lines, "adjacent:9001").unwrap();
+        assert_eq!(adjacent, "STHGRZZFR");
+    }
+
+    // cargo test --release bench_program -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_program_vs_regex() {
+        let mut next = crate::test_random_numbers(0x2022);
+        let stack_count = 9;
+        let mut heights = vec![100; stack_count];
+        let mut input = (0..100)
+            .map(|_| vec!["[A]"; stack_count].join(" "))
+            .collect::<Vec<String>>();
+        input.push(
+            (1..=stack_count)
+                .map(|label| format!(" {} ", label))
+                .collect::<Vec<String>>()
+                .join(" "),
+        );
+        input.push(String::new());
+        for _ in 0..1_000_000 {
+            let from = next(stack_count);
+            let to = (from + 1 + next(stack_count - 1)) % stack_count;
+            let count = next(heights[from].min(30) + 1);
+            heights[from] -= count;
+            heights[to] += count;
+            input.push(format!("move {} from {} to {}", count, from + 1, to + 1));
+        }
+
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+            let start = Instant::now();
+            let regex_result = follow_the_rules_regex(input.iter().cloned(), crane)
+                .unwrap()
+                .tops();
+            println!("{} regex: {:?}", crane, start.elapsed());
+            let start = Instant::now();
+            let program_result = follow_the_rules(input.iter().cloned(), crane)
+                .unwrap()
+                .tops();
+            println!("{} compiled Program: {:?}", crane, start.elapsed());
+            assert_eq!(regex_result, program_result);
+        }
+    }
 }
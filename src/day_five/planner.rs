@@ -0,0 +1,310 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+};
+
+use color_eyre::{Report, Result};
+
+use super::{crane::Crane, program::Move, stacks::CrateStacks};
+
+/// What the stacks should look like once the plan has been carried out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Target {
+    /// Every stack has to match exactly
+    Diagram(CrateStacks),
+    /// Only the crates on top matter
+    Tops(String),
+}
+
+impl Target {
+    fn is_reached(&self, stacks: &CrateStacks) -> bool {
+        match self {
+            Target::Diagram(target) => target == stacks,
+            Target::Tops(tops) => stacks.tops() == *tops,
+        }
+    }
+}
+
+/// How far the search is allowed to go before giving up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct PlannerLimits {
+    pub(super) max_moves: usize,
+    /// Number of different arrangements looked at
+    pub(super) max_states: usize,
+}
+
+impl Default for PlannerLimits {
+    fn default() -> Self {
+        PlannerLimits {
+            max_moves: 6,
+            max_states: 200_000,
+        }
+    }
+}
+
+/// A list of moves written out the same way as the puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Plan {
+    /// In the order they have to be done
+    pub(super) moves: Vec<Move>,
+}
+
+impl Plan {
+    /// The starting diagram, the blank line and then the moves, so it can be run like an input
+    pub(super) fn puzzle_text(&self, start: &CrateStacks) -> String {
+        format!("{}\n\n{}", start, self)
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .moves
+            .iter()
+            .map(Move::to_string)
+            .collect::<Vec<String>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// Breadth first search over every move `crane` could make, so the plan found is as short as
+/// possible. Errors when nothing is found within `limits`.
+pub(super) fn plan_moves(
+    start: &CrateStacks,
+    target: &Target,
+    crane: &dyn Crane,
+    limits: PlannerLimits,
+) -> Result<Plan> {
+    if let Target::Diagram(target) = target {
+        if target.labels() != start.labels() {
+            return Err(Report::msg("the target has different stack numbers"));
+        }
+        let sorted_crates = |stacks: &CrateStacks| {
+            let mut crates = stacks.stacks().concat();
+            crates.sort_unstable();
+            crates
+        };
+        if sorted_crates(target) != sorted_crates(start) {
+            return Err(Report::msg("the target doesn't have the same crates"));
+        }
+    }
+
+    if target.is_reached(start) {
+        return Ok(Plan { moves: Vec::new() });
+    }
+    let gave_up = || {
+        Report::msg(format!(
+            "gave up after looking at {} arrangements",
+            limits.max_states
+        ))
+    };
+    if limits.max_states <= 1 {
+        return Err(gave_up());
+    }
+
+    // Each state remembers the one it came from and the move that got it there
+    let mut states: Vec<(CrateStacks, Option<(usize, Move)>)> = vec![(start.clone(), None)];
+    let mut seen = HashSet::from([start.clone()]);
+    // (index into states, moves so far)
+    let mut queue = VecDeque::from([(0, 0)]);
+
+    while let Some((index, depth)) = queue.pop_front() {
+        if depth == limits.max_moves {
+            continue;
+        }
+
+        let labels = states[index].0.labels().to_vec();
+        for from in labels.iter().copied() {
+            for to in labels.iter().copied().filter(|to| *to != from) {
+                for count in 1..=states[index].0.stack(from)?.len() {
+                    let mut next = states[index].0.clone();
+                    // Some cranes can't do every move, those are just skipped
+                    if crane.apply(&mut next, count, from, to).is_err() || seen.contains(&next) {
+                        continue;
+                    }
+                    // Checked now rather than when it comes off the queue, so the last
+                    // arrangement allowed by the limit still gets compared with the target
+                    let reached = target.is_reached(&next);
+                    seen.insert(next.clone());
+                    states.push((next, Some((index, Move { count, from, to }))));
+                    if reached {
+                        return Ok(plan_to(&states, states.len() - 1));
+                    }
+                    if seen.len() == limits.max_states {
+                        return Err(gave_up());
+                    }
+                    queue.push_back((states.len() - 1, depth + 1));
+                }
+            }
+        }
+    }
+
+    Err(Report::msg(format!(
+        "no plan with at most {} moves exists",
+        limits.max_moves
+    )))
+}
+
+/// Follows the states back to the start to get the moves that led to `index`
+fn plan_to(states: &[(CrateStacks, Option<(usize, Move)>)], index: usize) -> Plan {
+    let mut moves = Vec::new();
+    let mut current = index;
+    while let Some((parent, step)) = states[current].1 {
+        moves.push(step);
+        current = parent;
+    }
+    moves.reverse();
+    Plan { moves }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_five::{
+        crane::{crane_by_name, CrateMover9000, CrateMover9001},
+        follow_the_rules_with_crane,
+    };
+
+    fn diagram(lines: &[&str]) -> CrateStacks {
+        CrateStacks::parse_diagram(
+            &lines
+                .iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>(),
+            1,
+        )
+        .unwrap()
+    }
+
+    fn example() -> CrateStacks {
+        diagram(&["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "])
+    }
+
+    #[test]
+    fn test_plan_to_diagram() {
+        let target = diagram(&["[D]        ", "[N]     [C]", "[Z] [M] [P]", " 1   2   3 "]);
+        for name in ["9000", "9001", "chunked:2", "adjacent:9001"] {
+            let crane = crane_by_name(name).unwrap();
+            let plan = plan_moves(
+                &example(),
+                &Target::Diagram(target.clone()),
+                crane.as_ref(),
+                PlannerLimits::default(),
+            )
+            .unwrap();
+            assert_eq!(plan.moves.len(), 2, "{}: {}", crane, plan);
+
+            let mut stacks = example();
+            for next in plan.moves.iter() {
+                crane
+                    .apply(&mut stacks, next.count, next.from, next.to)
+                    .unwrap();
+            }
+            assert_eq!(stacks, target);
+
+            // The text it gives back is a valid input
+            let text = plan.puzzle_text(&example());
+            let lines = text.split('\n').map(str::to_string);
+            assert_eq!(follow_the_rules_with_crane(lines, name).unwrap(), "DMC");
+        }
+    }
+
+    #[test]
+    fn test_plan_to_tops() {
+        let plan = plan_moves(
+            &example(),
+            &Target::Tops("CMZ".to_string()),
+            &CrateMover9000,
+            PlannerLimits::default(),
+        )
+        .unwrap();
+        assert!(plan.moves.len() <= 4);
+        let text = plan.puzzle_text(&example());
+        assert_eq!(
+            follow_the_rules_with_crane(text.split('\n').map(str::to_string), "9000").unwrap(),
+            "CMZ"
+        );
+
+        let plan = plan_moves(
+            &example(),
+            &Target::Tops("NDP".to_string()),
+            &CrateMover9001,
+            PlannerLimits::default(),
+        )
+        .unwrap();
+        assert_eq!(plan.to_string(), "");
+    }
+
+    #[test]
+    fn test_no_plan() {
+        let target = Target::Tops("ZMP".to_string());
+        let limits = PlannerLimits {
+            max_moves: 1,
+            ..PlannerLimits::default()
+        };
+        let err = plan_moves(&example(), &target, &CrateMover9001, limits).unwrap_err();
+        assert_eq!(err.to_string(), "no plan with at most 1 moves exists");
+
+        let limits = PlannerLimits {
+            max_moves: 10,
+            max_states: 5,
+        };
+        assert!(plan_moves(&example(), &target, &CrateMover9001, limits).is_err());
+
+        let target = Target::Diagram(diagram(&["[A]", " 1   2   3"]));
+        assert!(plan_moves(
+            &example(),
+            &target,
+            &CrateMover9001,
+            PlannerLimits::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_goal_at_state_limit() {
+        // Moving N onto stack 2 is the first thing tried, so it's the second arrangement
+        let target = Target::Tops("ZNP".to_string());
+        let limits = PlannerLimits {
+            max_moves: 1,
+            max_states: 2,
+        };
+        let plan = plan_moves(&example(), &target, &CrateMover9001, limits).unwrap();
+        assert_eq!(plan.to_string(), "move 1 from 1 to 2");
+
+        let limits = PlannerLimits {
+            max_states: 1,
+            ..limits
+        };
+        let err = plan_moves(&example(), &target, &CrateMover9001, limits).unwrap_err();
+        assert_eq!(err.to_string(), "gave up after looking at 1 arrangements");
+
+        // Every arrangement one move away, in the order the search finds them
+        let mut found = vec![example()];
+        for from in 1..=3 {
+            for to in (1..=3).filter(|to| *to != from) {
+                for count in 1..=example().stack(from).unwrap().len() {
+                    let mut next = example();
+                    CrateMover9001.apply(&mut next, count, from, to).unwrap();
+                    if !found.contains(&next) {
+                        found.push(next);
+                    }
+                }
+            }
+        }
+        for (position, stacks) in found.iter().enumerate().skip(1) {
+            let target = Target::Diagram(stacks.clone());
+            let limits = PlannerLimits {
+                max_moves: 1,
+                max_states: position + 1,
+            };
+            let plan = plan_moves(&example(), &target, &CrateMover9001, limits).unwrap();
+            assert_eq!(plan.moves.len(), 1);
+            let limits = PlannerLimits {
+                max_states: position,
+                ..limits
+            };
+            assert!(plan_moves(&example(), &target, &CrateMover9001, limits).is_err());
+        }
+    }
+}
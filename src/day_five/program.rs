@@ -0,0 +1,219 @@
+use std::{fmt::Display, str::FromStr};
+
+use color_eyre::{Report, Result};
+
+use super::{crane::Crane, stacks::CrateStacks};
+
+/// One line of the rearrangement procedure, `move <count> from <from> to <to>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct Move {
+    pub(super) count: usize,
+    pub(super) from: usize,
+    pub(super) to: usize,
+}
+
+impl FromStr for Move {
+    type Err = Report;
+
+    /// Goes word by word instead of using a regex since this runs for every line
+    fn from_str(s: &str) -> Result<Self> {
+        let mut words = s.split_ascii_whitespace();
+        let mut number_after = |keyword: &str| -> Result<usize> {
+            match words.next() {
+                Some(word) if word == keyword => {}
+                other => {
+                    return Err(Report::msg(format!(
+                        "expected {:?} but found {:?} in {:?}",
+                        keyword, other, s
+                    )))
+                }
+            }
+            let number = words.next().ok_or_else(|| {
+                Report::msg(format!("expected a number after {:?} in {:?}", keyword, s))
+            })?;
+            number.parse::<usize>().map_err(|_| {
+                Report::msg(format!(
+                    "expected a number after {:?} but found {:?}",
+                    keyword, number
+                ))
+            })
+        };
+        let count = number_after("move")?;
+        let from = number_after("from")?;
+        let to = number_after("to")?;
+        if words.next().is_some() {
+            return Err(Report::msg(format!(
+                "unexpected words at the end of {:?}",
+                s
+            )));
+        }
+        Ok(Move { count, from, to })
+    }
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} from {} to {}", self.count, self.from, self.to)
+    }
+}
+
+/// Parses every remaining line as a move, `first_line_number` is only for error messages
+pub(super) fn read_moves<I>(input_iterator: I, first_line_number: usize) -> Result<Vec<Move>>
+where
+    I: Iterator<Item = String>,
+{
+    input_iterator
+        .zip(first_line_number..)
+        .map(|(line, line_number)| {
+            line.parse::<Move>()
+                .map_err(|err| Report::msg(format!("line {}: {}", line_number, err)))
+        })
+        .collect()
+}
+
+/// A move whose stacks are known to exist, with the line it came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CompiledMove {
+    /// Where the move was in the input, for error messages
+    line_number: usize,
+    next: Move,
+}
+
+/// The whole procedure checked against the stacks once up front, so a missing stack is found
+/// before anything has been moved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Program {
+    moves: Vec<CompiledMove>,
+}
+
+impl Program {
+    /// `moves` are one per line starting at `first_line_number`, like [`read_moves`] gives back
+    pub(super) fn compile(
+        stacks: &CrateStacks,
+        moves: &[Move],
+        first_line_number: usize,
+    ) -> Result<Program> {
+        Ok(Program {
+            moves: moves
+                .iter()
+                .zip(first_line_number..)
+                .map(|(next, line_number)| {
+                    for label in [next.from, next.to] {
+                        stacks
+                            .index_of(label)
+                            .map_err(|err| Report::msg(format!("line {}: {}", line_number, err)))?;
+                    }
+                    Ok(CompiledMove {
+                        line_number,
+                        next: *next,
+                    })
+                })
+                .collect::<Result<Vec<CompiledMove>>>()?,
+        })
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Runs every move on `stacks` with `crane`
+    pub(super) fn run(&self, stacks: &mut CrateStacks, crane: &dyn Crane) -> Result<()> {
+        for CompiledMove { line_number, next } in self.moves.iter() {
+            crane
+                .apply(stacks, next.count, next.from, next.to)
+                .map_err(|err| Report::msg(format!("line {}: {}", line_number, err)))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_five::crane::{CrateMover9000, CrateMover9001};
+
+    #[test]
+    fn test_parse_move() {
+        assert_eq!(
+            "move 12 from 2 to 10".parse::<Move>().unwrap(),
+            Move {
+                count: 12,
+                from: 2,
+                to: 10
+            }
+        );
+        assert_eq!(
+            "move 1 from 2 to 1".parse::<Move>().unwrap().to_string(),
+            "move 1 from 2 to 1"
+        );
+        for bad in [
+            "",
+            "move 1 from 2",
+            "move 1 from 2 to",
+            "move a from 2 to 1",
+            "move 1 to 2 from 1",
+            "move 1 from 2 to 1 please",
+            "move -1 from 2 to 1",
+        ] {
+            assert!(bad.parse::<Move>().is_err(), "{:?}", bad);
+        }
+
+        let err = read_moves(
+            ["move 1 from 2 to 1", "move 1"]
+                .map(str::to_string)
+                .into_iter(),
+            6,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("line 7: "), "{}", err);
+    }
+
+    #[test]
+    fn test_program() {
+        let lines =
+            ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "].map(str::to_string);
+        let stacks = CrateStacks::parse_diagram(&lines, 1).unwrap();
+        let moves = read_moves(
+            [
+                "move 1 from 2 to 1",
+                "move 3 from 1 to 3",
+                "move 2 from 2 to 1",
+                "move 1 from 1 to 2",
+            ]
+            .map(str::to_string)
+            .into_iter(),
+            6,
+        )
+        .unwrap();
+        let program = Program::compile(&stacks, &moves, 6).unwrap();
+        assert_eq!(program.len(), 4);
+
+        let mut one_at_a_time = stacks.clone();
+        program.run(&mut one_at_a_time, &CrateMover9000).unwrap();
+        assert_eq!(one_at_a_time.tops(), "CMZ");
+        let mut as_a_block = stacks.clone();
+        program.run(&mut as_a_block, &CrateMover9001).unwrap();
+        assert_eq!(as_a_block.tops(), "MCD");
+
+        let moves = [Move {
+            count: 1,
+            from: 4,
+            to: 1,
+        }];
+        let err = Program::compile(&stacks, &moves, 6).unwrap_err();
+        assert!(err.to_string().starts_with("line 6: "), "{}", err);
+        let moves = [Move {
+            count: 3,
+            from: 3,
+            to: 1,
+        }];
+        let program = Program::compile(&stacks, &moves, 9).unwrap();
+        let err = program
+            .run(&mut stacks.clone(), &CrateMover9001)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 9: No container found at expected location, stack 3 has 1 crates but 3 are needed"
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use color_eyre::{Report, Result};
+
+use super::{
+    crane::Crane,
+    program::{read_moves, Move},
+    read_initial_state,
+    stacks::CrateStacks,
+};
+
+/// One line of the rearrangement procedure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct ReplayMove {
+    pub(super) line_number: usize,
+    pub(super) count: usize,
+    pub(super) from: usize,
+    pub(super) to: usize,
+}
+
+/// How many moves apart the states kept by [`Replay`] are
+const CHECKPOINT_EVERY: usize = 64;
+
+/// Steps through the procedure one move at a time. Only every [`CHECKPOINT_EVERY`]th state is
+/// kept, anything else is rebuilt from the closest one before it when moves are undone.
+pub(super) struct Replay<'a> {
+    crane: &'a dyn Crane,
+    moves: Vec<ReplayMove>,
+    /// `checkpoints[n]` is what the stacks look like after `n * CHECKPOINT_EVERY` moves
+    checkpoints: Vec<CrateStacks>,
+    position: usize,
+    state: CrateStacks,
+}
+
+impl<'a> Replay<'a> {
+    pub(super) fn new<I>(mut input_iterator: I, crane: &'a dyn Crane) -> Result<Replay<'a>>
+    where
+        I: Iterator<Item = String>,
+    {
+        let (initial, lines_used) = read_initial_state(&mut input_iterator)?;
+        let moves = read_moves(input_iterator, lines_used + 1)?
+            .into_iter()
+            .zip(lines_used + 1..)
+            .map(|(Move { count, from, to }, line_number)| ReplayMove {
+                line_number,
+                count,
+                from,
+                to,
+            })
+            .collect();
+        Ok(Replay {
+            crane,
+            moves,
+            checkpoints: vec![initial.clone()],
+            position: 0,
+            state: initial,
+        })
+    }
+
+    pub(super) fn moves(&self) -> &[ReplayMove] {
+        &self.moves
+    }
+
+    /// How many moves have been applied to get to [`Replay::state`]
+    pub(super) fn position(&self) -> usize {
+        self.position
+    }
+
+    pub(super) fn state(&self) -> &CrateStacks {
+        &self.state
+    }
+
+    pub(super) fn is_finished(&self) -> bool {
+        self.position == self.moves.len()
+    }
+
+    /// Applies the next move, false if there weren't any left
+    pub(super) fn step(&mut self) -> Result<bool> {
+        let Some(next) = self.moves.get(self.position).copied() else {
+            return Ok(false);
+        };
+        let mut state = self.state.clone();
+        self.crane
+            .apply(&mut state, next.count, next.from, next.to)
+            .map_err(|err| Report::msg(format!("line {}: {}", next.line_number, err)))?;
+        self.state = state;
+        self.position += 1;
+        if self.position == self.checkpoints.len() * CHECKPOINT_EVERY {
+            self.checkpoints.push(self.state.clone());
+        }
+        Ok(true)
+    }
+
+    /// Goes back one move, false if already at the start
+    pub(super) fn undo(&mut self) -> Result<bool> {
+        if self.position == 0 {
+            return Ok(false);
+        }
+        self.seek(self.position - 1)?;
+        Ok(true)
+    }
+
+    /// Goes forwards or backwards until exactly `position` moves have been applied
+    pub(super) fn seek(&mut self, position: usize) -> Result<()> {
+        if position > self.moves.len() {
+            return Err(Report::msg(format!(
+                "can't seek to move {}, there are only {}",
+                position,
+                self.moves.len()
+            )));
+        }
+        // Start again from the last checkpoint unless the current state is closer
+        let checkpoint = (position / CHECKPOINT_EVERY).min(self.checkpoints.len() - 1);
+        if position < self.position || checkpoint * CHECKPOINT_EVERY > self.position {
+            self.state = self.checkpoints[checkpoint].clone();
+            self.position = checkpoint * CHECKPOINT_EVERY;
+        }
+        while self.position < position {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// The index in [`Replay::moves`] of the first move that lifts the crate that started on
+    /// stack `label` at `height` (1 is the bottom). Crates are tracked by where they started
+    /// rather than by letter since the same letter can be on more than one crate.
+    pub(super) fn first_move_touching(&self, label: usize, height: usize) -> Result<Option<usize>> {
+        let initial = &self.checkpoints[0];
+        if height == 0 || initial.stack(label)?.len() < height {
+            return Err(Report::msg(format!(
+                "stack {} doesn't start with a crate at height {}",
+                label, height
+            )));
+        }
+
+        // The same moves on crates that are all named differently
+
+        let mut tracked = CrateStacks::new(
+            initial.labels().to_vec(),
+            initial
+                .labels()
+                .iter()
+                .zip(initial.stacks())
+                .map(|(stack_label, stack)| {
+                    (1..=stack.len())
+                        .map(|crate_height| format!("{}-{}", stack_label, crate_height))
+                        .collect()
+                })
+                .collect(),
+        )?;
+        let wanted = format!("{}-{}", label, height);
+
+        for (index, next) in self.moves.iter().enumerate() {
+            let lifted = tracked.stack(next.from)?;
+            if lifted
+                .iter()
+                .skip(lifted.len().saturating_sub(next.count))
+                .any(|tracked_crate| *tracked_crate == wanted)
+            {
+                return Ok(Some(index));
+            }
+            self.crane
+                .apply(&mut tracked, next.count, next.from, next.to)
+                .map_err(|err| Report::msg(format!("line {}: {}", next.line_number, err)))?;
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_five::crane::{CrateMover9000, CrateMover9001};
+
+    fn example() -> impl Iterator<Item = String> {
+        [
+            "    [D]    ",
+            "[N] [C]    ",
+            "[Z] [M] [P]",
+            " 1   2   3 ",
+            "",
+            "move 1 from 2 to 1",
+            "move 3 from 1 to 3",
+            "move 2 from 2 to 1",
+            "move 1 from 1 to 2",
+        ]
+        .into_iter()
+        .map(str::to_string)
+    }
+
+    #[test]
+    fn test_step_and_undo() {
+        let mut replay = Replay::new(example(), &CrateMover9000).unwrap();
+        assert_eq!(replay.moves()[0].line_number, 6);
+        assert_eq!(replay.state().tops(), "NDP");
+
+        assert!(replay.step().unwrap());
+        assert_eq!(replay.state().tops(), "DCP");
+        assert!(replay.step().unwrap());
+        assert_eq!(replay.state().tops(), "CZ");
+        assert!(replay.undo().unwrap());
+        assert_eq!(replay.state().tops(), "DCP");
+        assert!(replay.undo().unwrap());
+        assert!(!replay.undo().unwrap());
+        assert_eq!(replay.position(), 0);
+
+        replay.seek(4).unwrap();
+        assert!(replay.is_finished());
+        assert!(!replay.step().unwrap());
+        assert_eq!(replay.state().tops(), "CMZ");
+        replay.seek(2).unwrap();
+        assert_eq!(replay.state().tops(), "CZ");
+        assert!(replay.seek(5).is_err());
+    }
+
+    #[test]
+    fn test_first_move_touching() {
+        let replay = Replay::new(example(), &CrateMover9001).unwrap();
+        // D is lifted first
+        assert_eq!(replay.first_move_touching(2, 3).unwrap(), Some(0));
+        // Z goes along with the 3 crates on stack 1
+        assert_eq!(replay.first_move_touching(1, 1).unwrap(), Some(1));
+        assert_eq!(replay.first_move_touching(2, 1).unwrap(), Some(2));
+        // P is buried on stack 3 and never moves
+        assert_eq!(replay.first_move_touching(3, 1).unwrap(), None);
+        assert!(replay.first_move_touching(3, 2).is_err());
+        assert!(replay.first_move_touching(4, 1).is_err());
+    }
+
+    #[test]
+    fn test_replay_my_input() {
+        let lines = crate::read_file_line_by_line("src/day_five/input.txt").unwrap();
+        let mut replay = Replay::new(lines, &CrateMover9000).unwrap();
+        let total = replay.moves().len();
+        assert!(total > 2 * CHECKPOINT_EVERY);
+        replay.seek(total).unwrap();
+        assert_eq!(replay.state().tops(), "RTGWZTHLD");
+        let finished = replay.state().clone();
+
+        // Going back past checkpoints and forwards again ends up in the same place
+        replay.seek(CHECKPOINT_EVERY + 1).unwrap();
+        let mut stepped = Replay::new(
+            crate::read_file_line_by_line("src/day_five/input.txt").unwrap(),
+            &CrateMover9000,
+        )
+        .unwrap();
+        while stepped.position() < CHECKPOINT_EVERY + 1 {
+            stepped.step().unwrap();
+        }
+        assert_eq!(replay.state(), stepped.state());
+        assert!(replay.undo().unwrap());
+        assert!(replay.undo().unwrap());
+        assert_eq!(replay.position(), CHECKPOINT_EVERY - 1);
+        replay.seek(total).unwrap();
+        assert_eq!(*replay.state(), finished);
+
+        replay.seek(0).unwrap();
+        assert_eq!(replay.state().to_string().lines().count(), 9);
+    }
+}
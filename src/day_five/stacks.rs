@@ -0,0 +1,374 @@
+use std::fmt::Display;
+
+use color_eyre::{Report, Result};
+
+/// What's written between the brackets, usually a single letter
+pub(super) type Crate = String;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) struct CrateStacks {
+    /// The numbers from the footer line, in the same order as `stacks`
+    labels: Vec<usize>,
+    /// Bottom crate first so the top is the end of the Vec
+    stacks: Vec<Vec<Crate>>,
+}
+
+fn diagram_error(line_number: usize, column: usize, msg: impl std::fmt::Display) -> Report {
+    Report::msg(format!("line {}, column {}: {}", line_number, column, msg))
+}
+
+impl CrateStacks {
+    pub(super) fn new(labels: Vec<usize>, stacks: Vec<Vec<Crate>>) -> Result<CrateStacks> {
+        if labels.len() != stacks.len() {
+            return Err(Report::msg(format!(
+                "{} stack numbers given for {} stacks",
+                labels.len(),
+                stacks.len()
+            )));
+        }
+        Ok(CrateStacks { labels, stacks })
+    }
+
+    /// Parses the drawing of the stacks. The last line has to be the stack numbers, which is
+    /// what decides where each column is.
+    ///
+    /// `first_line_number` is only used for error messages.
+    pub(super) fn parse_diagram(lines: &[String], first_line_number: usize) -> Result<CrateStacks> {
+        let (footer, crate_lines) = lines
+            .split_last()
+            .ok_or_else(|| Report::msg("No lines found for the initial setup state"))?;
+        let footer_line_number = first_line_number + crate_lines.len();
+
+        // (label, first column, last column)
+        let mut footer_labels: Vec<(usize, usize, usize)> = Vec::new();
+        let footer = footer.chars().collect::<Vec<char>>();
+        let mut column = 0;
+        while column < footer.len() {
+            if footer[column].is_whitespace() {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < footer.len() && !footer[column].is_whitespace() {
+                column += 1;
+            }
+            let label = footer[start..column].iter().collect::<String>();
+            let label = label.parse::<usize>().map_err(|_| {
+                diagram_error(
+                    footer_line_number,
+                    start + 1,
+                    format!("expected a stack number but found {:?}", label),
+                )
+            })?;
+            if footer_labels.iter().any(|(other, _, _)| *other == label) {
+                return Err(diagram_error(
+                    footer_line_number,
+                    start + 1,
+                    format!("stack {} is numbered twice", label),
+                ));
+            }
+            footer_labels.push((label, start, column - 1));
+        }
+        if footer_labels.is_empty() {
+            return Err(diagram_error(
+                footer_line_number,
+                1,
+                "no stack numbers found",
+            ));
+        }
+
+        let mut stacks = vec![Vec::new(); footer_labels.len()];
+        // rev so the top crate is pushed last
+        for (index, line) in crate_lines.iter().enumerate().rev() {
+            let line_number = first_line_number + index;
+            let line = line.chars().collect::<Vec<char>>();
+            let mut column = 0;
+            while column < line.len() {
+                match line[column] {
+                    ' ' | '\t' => column += 1,
+                    '[' => {
+                        let open = column;
+                        let close = (open + 1..line.len())
+                            .find(|index| line[*index] == ']')
+                            .ok_or_else(|| {
+                                diagram_error(line_number, open + 1, "crate is missing its ]")
+                            })?;
+                        let label = line[open + 1..close].iter().collect::<String>();
+                        if label.trim().is_empty() {
+                            return Err(diagram_error(line_number, open + 1, "crate has no label"));
+                        }
+                        let mut lined_up = footer_labels
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, (_, start, end))| *start <= close && open <= *end)
+                            .map(|(index, _)| index);
+                        let stack_index = match (lined_up.next(), lined_up.next()) {
+                            (Some(stack_index), None) => stack_index,
+                            (None, _) => {
+                                return Err(diagram_error(
+                                    line_number,
+                                    open + 1,
+                                    format!("crate [{}] isn't above any stack number", label),
+                                ))
+                            }
+                            (Some(_), Some(_)) => {
+                                return Err(diagram_error(
+                                    line_number,
+                                    open + 1,
+                                    format!(
+                                        "crate [{}] is above more than one stack number",
+                                        label
+                                    ),
+                                ))
+                            }
+                        };
+                        stacks[stack_index].push(label);
+                        column = close + 1;
+                    }
+                    other => {
+                        return Err(diagram_error(
+                            line_number,
+                            column + 1,
+                            format!("unexpected {:?}, crates are written like [A]", other),
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(CrateStacks {
+            labels: footer_labels
+                .into_iter()
+                .map(|(label, _, _)| label)
+                .collect(),
+            stacks,
+        })
+    }
+
+    pub(super) fn labels(&self) -> &[usize] {
+        &self.labels
+    }
+
+    pub(super) fn stacks(&self) -> &[Vec<Crate>] {
+        &self.stacks
+    }
+
+    /// Position in [`CrateStacks::stacks`] of the stack with the number `label`
+    pub(super) fn index_of(&self, label: usize) -> Result<usize> {
+        self.labels
+            .iter()
+            .position(|other| *other == label)
+            .ok_or_else(|| Report::msg(format!("no stack numbered {}", label)))
+    }
+
+    pub(super) fn stack(&self, label: usize) -> Result<&Vec<Crate>> {
+        Ok(&self.stacks[self.index_of(label)?])
+    }
+
+    pub(super) fn stack_mut(&mut self, label: usize) -> Result<&mut Vec<Crate>> {
+        let index = self.index_of(label)?;
+        Ok(&mut self.stacks[index])
+    }
+
+    /// Lifts the top `count` crates off a stack as one block, bottom crate first
+    pub(super) fn take(&mut self, label: usize, count: usize) -> Result<Vec<Crate>> {
+        let stack = self.stack_mut(label)?;
+        if stack.len() < count {
+            return Err(Report::msg(format!(
+                "No container found at expected location, stack {} has {} crates but {} are needed",
+                label,
+                stack.len(),
+                count
+            )));
+        }
+        let at = stack.len() - count;
+        Ok(stack.split_off(at))
+    }
+
+    /// Sets a block down on a stack, the last crate of `crates` ends up on top
+    pub(super) fn put(&mut self, label: usize, crates: Vec<Crate>) -> Result<()> {
+        self.stack_mut(label)?.extend(crates);
+        Ok(())
+    }
+
+    /// The top crate of every stack joined together, empty stacks are skipped
+    pub(super) fn tops(&self) -> String {
+        self.stacks
+            .iter()
+            .filter_map(|stack| stack.last())
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Draws the stacks the same way the puzzle input does, so the output can be parsed back with
+/// [`CrateStacks::parse_diagram`]. Every line is padded out to the full width.
+impl Display for CrateStacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let labels = self
+            .labels
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<String>>();
+        let widths = self
+            .stacks
+            .iter()
+            .zip(labels.iter())
+            .map(|(stack, label)| {
+                stack
+                    .iter()
+                    .map(|crate_label| crate_label.chars().count() + 2)
+                    .chain([label.len(), 3])
+                    .max()
+                    .unwrap_or(3)
+            })
+            .collect::<Vec<usize>>();
+        // Centered so the crate and its number share a column
+        let cell = |text: &str, width: usize| {
+            let len = text.chars().count();
+            let before = (width - len) / 2;
+            format!(
+                "{}{}{}",
+                " ".repeat(before),
+                text,
+                " ".repeat(width - len - before)
+            )
+        };
+
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        for row in (0..height).rev() {
+            let line = self
+                .stacks
+                .iter()
+                .zip(widths.iter())
+                .map(|(stack, width)| match stack.get(row) {
+                    Some(crate_label) => cell(&format!("[{}]", crate_label), *width),
+                    None => " ".repeat(*width),
+                })
+                .collect::<Vec<String>>();
+            writeln!(f, "{}", line.join(" "))?;
+        }
+        let footer = labels
+            .iter()
+            .zip(widths.iter())
+            .map(|(label, width)| cell(label, *width))
+            .collect::<Vec<String>>();
+        write!(f, "{}", footer.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(diagram: &[&str]) -> Vec<String> {
+        diagram.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_trimmed_lines() {
+        let stacks = CrateStacks::parse_diagram(
+            &lines(&["    [D]", "[N] [C]", "[Z] [M] [P]", " 1   2   3"]),
+            1,
+        )
+        .unwrap();
+        assert_eq!(stacks.labels(), &[1, 2, 3]);
+        assert_eq!(stacks.stack(2).unwrap(), &vec!["M", "C", "D"]);
+        assert_eq!(stacks.tops(), "NDP");
+    }
+
+    #[test]
+    fn test_parse_more_than_nine_stacks() {
+        let stacks = CrateStacks::parse_diagram(
+            &lines(&[
+                "                                        [K]",
+                "[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [L]",
+                " 1   2   3   4   5   6   7   8   9  10  11",
+            ]),
+            1,
+        )
+        .unwrap();
+        assert_eq!(stacks.stacks().len(), 11);
+        assert_eq!(stacks.stack(11).unwrap(), &vec!["L", "K"]);
+        assert_eq!(stacks.tops(), "ABCDEFGHIJK");
+    }
+
+    #[test]
+    fn test_parse_multi_character_crates() {
+        let stacks =
+            CrateStacks::parse_diagram(&lines(&["      [XY]", "[AB]  [CD]", " 1     2"]), 1)
+                .unwrap();
+        assert_eq!(stacks.stack(1).unwrap(), &vec!["AB"]);
+        assert_eq!(stacks.stack(2).unwrap(), &vec!["CD", "XY"]);
+    }
+
+    #[test]
+    fn test_display() {
+        let stacks = CrateStacks::parse_diagram(
+            &lines(&["    [D]", "[N] [C]", "[Z] [M] [P]", " 1   2   3"]),
+            1,
+        )
+        .unwrap();
+        assert_eq!(
+            stacks.to_string(),
+            "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+        );
+
+        let stacks = CrateStacks::new(
+            vec![1, 2, 10],
+            vec![vec!["AB".to_string()], vec![], vec!["C".to_string()]],
+        )
+        .unwrap();
+        assert_eq!(stacks.to_string(), "[AB]     [C]\n 1    2  10 ");
+        let lines = stacks
+            .to_string()
+            .lines()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(CrateStacks::parse_diagram(&lines, 1).unwrap(), stacks);
+    }
+
+    #[test]
+    fn test_display_round_trip_my_input() {
+        let mut input = crate::read_file_line_by_line("src/day_five/input.txt").unwrap();
+        let diagram = input
+            .by_ref()
+            .take_while(|line| !line.trim().is_empty())
+            .collect::<Vec<String>>();
+        let mut stacks = CrateStacks::parse_diagram(&diagram, 1).unwrap();
+        assert_eq!(stacks.to_string(), diagram.join("\n"));
+
+        // Every state along the way has to survive being drawn and parsed again
+        for line in input {
+            let numbers = line
+                .split(' ')
+                .filter_map(|word| word.parse::<usize>().ok())
+                .collect::<Vec<usize>>();
+            for _ in 0..numbers[0] {
+                let moved = stacks.stack_mut(numbers[1]).unwrap().pop().unwrap();
+                stacks.stack_mut(numbers[2]).unwrap().push(moved);
+            }
+            let drawn = stacks
+                .to_string()
+                .lines()
+                .map(str::to_string)
+                .collect::<Vec<_>>();
+            assert_eq!(CrateStacks::parse_diagram(&drawn, 1).unwrap(), stacks);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = CrateStacks::parse_diagram(&lines(&["[A] [B", " 1   2"]), 3).unwrap_err();
+        assert_eq!(err.to_string(), "line 3, column 5: crate is missing its ]");
+
+        let err = CrateStacks::parse_diagram(&lines(&["[A] B", " 1   2"]), 1).unwrap_err();
+        assert!(err.to_string().starts_with("line 1, column 5"), "{}", err);
+
+        let err = CrateStacks::parse_diagram(&lines(&["[A]     [C]", " 1   2"]), 1).unwrap_err();
+        assert!(err.to_string().starts_with("line 1, column 9"), "{}", err);
+
+        let err = CrateStacks::parse_diagram(&lines(&["[A]", " 1   x"]), 1).unwrap_err();
+        assert!(err.to_string().starts_with("line 2, column 6"), "{}", err);
+    }
+}
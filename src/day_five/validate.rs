@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+use color_eyre::{Report, Result};
+
+use super::{
+    program::{read_moves, Move},
+    read_initial_state,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum MoveProblem {
+    NotEnoughCrates { needed: usize, available: usize },
+    UnknownStack(usize),
+    SameStack,
+    ZeroCount,
+}
+
+impl Display for MoveProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveProblem::NotEnoughCrates { needed, available } => write!(
+                f,
+                "needs {} crates but only {} are there ({} short)",
+                needed,
+                available,
+                needed - available
+            ),
+            MoveProblem::UnknownStack(label) => write!(f, "there is no stack {}", label),
+            MoveProblem::SameStack => write!(f, "moves crates onto the stack they came from"),
+            MoveProblem::ZeroCount => write!(f, "doesn't move any crates"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct InvalidMove {
+    pub(super) line_number: usize,
+    pub(super) count: usize,
+    pub(super) from: usize,
+    pub(super) to: usize,
+    /// Height of every stack just before this move, in the same order as the diagram
+    pub(super) heights: Vec<usize>,
+    pub(super) problem: MoveProblem,
+}
+
+impl InvalidMove {
+    /// How many more crates the source stack would need for the move to work
+    pub(super) fn crates_short(&self) -> usize {
+        match self.problem {
+            MoveProblem::NotEnoughCrates { needed, available } => needed - available,
+            _ => 0,
+        }
+    }
+}
+
+impl Display for InvalidMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: move {} from {} to {} {}, heights were {}",
+            self.line_number,
+            self.count,
+            self.from,
+            self.to,
+            self.problem,
+            self.heights
+                .iter()
+                .map(usize::to_string)
+                .collect::<Vec<String>>()
+                .join(" ")
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ValidationReport {
+    pub(super) moves_checked: usize,
+    pub(super) problems: Vec<InvalidMove>,
+}
+
+impl ValidationReport {
+    pub(super) fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} moves checked, {} problems",
+            self.moves_checked,
+            self.problems.len()
+        )?;
+        for problem in self.problems.iter() {
+            writeln!(f, "{}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks every move of the procedure without running it. Only the stack heights are tracked,
+/// which is all any crane needs to know whether a move can be done. A move that can't be done is
+/// left out so the rest of the procedure is still checked against sensible heights.
+pub(super) fn validate_procedure<I>(mut input_iterator: I) -> Result<ValidationReport>
+where
+    I: Iterator<Item = String>,
+{
+    let (initial, lines_used) = read_initial_state(&mut input_iterator)?;
+    let labels = initial.labels().to_vec();
+    let mut heights = initial.stacks().iter().map(Vec::len).collect::<Vec<_>>();
+
+    let mut report = ValidationReport::default();
+    for (Move { count, from, to }, line_number) in read_moves(input_iterator, lines_used + 1)?
+        .into_iter()
+        .zip(lines_used + 1..)
+    {
+        report.moves_checked += 1;
+
+        let from_index = labels.iter().position(|label| *label == from);
+        let to_index = labels.iter().position(|label| *label == to);
+        let problem = match (from_index, to_index) {
+            (None, _) => Some(MoveProblem::UnknownStack(from)),
+            (_, None) => Some(MoveProblem::UnknownStack(to)),
+            _ if count == 0 => Some(MoveProblem::ZeroCount),
+            _ if from == to => Some(MoveProblem::SameStack),
+            (Some(from_index), Some(to_index)) => {
+                if heights[from_index] < count {
+                    Some(MoveProblem::NotEnoughCrates {
+                        needed: count,
+                        available: heights[from_index],
+                    })
+                } else {
+                    heights[from_index] -= count;
+                    heights[to_index] += count;
+                    None
+                }
+            }
+        };
+        if let Some(problem) = problem {
+            report.problems.push(InvalidMove {
+                line_number,
+                count,
+                from,
+                to,
+                heights: heights.clone(),
+                problem,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> impl Iterator<Item = String> + '_ {
+        input.split('\n').map(str::to_string)
+    }
+
+    #[test]
+    fn test_validate_example() {
+        let input = "    [D]    
+[N] [C]    
+[Z] [M] [P]
+ 1   2   3 
+
+move 1 from 2 to 1
+move 3 from 1 to 3
+move 2 from 2 to 1
+move 1 from 1 to 2";
+        let report = validate_procedure(lines(input)).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.moves_checked, 4);
+
+        let report =
+            validate_procedure(crate::read_file_line_by_line("src/day_five/input.txt").unwrap())
+                .unwrap();
+        assert!(report.is_valid(), "{}", report);
+    }
+
+    #[test]
+    fn test_validate_problems() {
+        let input = "[N] [C]
+[Z] [M]
+ 1   2 
+
+move 3 from 1 to 2
+move 1 from 1 to 2
+move 0 from 1 to 2
+move 1 from 2 to 2
+move 1 from 1 to 3
+move 4 from 2 to 1";
+        let report = validate_procedure(lines(input)).unwrap();
+        assert_eq!(report.moves_checked, 6);
+        let problems = report
+            .problems
+            .iter()
+            .map(|problem| (problem.line_number, problem.problem))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            vec![
+                (
+                    5,
+                    MoveProblem::NotEnoughCrates {
+                        needed: 3,
+                        available: 2
+                    }
+                ),
+                (7, MoveProblem::ZeroCount),
+                (8, MoveProblem::SameStack),
+                (9, MoveProblem::UnknownStack(3)),
+                (
+                    10,
+                    MoveProblem::NotEnoughCrates {
+                        needed: 4,
+                        available: 3
+                    }
+                ),
+            ]
+        );
+        assert_eq!(report.problems[0].crates_short(), 1);
+        assert_eq!(
+            report.problems[0].to_string(),
+            "line 5: move 3 from 1 to 2 needs 3 crates but only 2 are there (1 short), heights were 2 2"
+        );
+        // The move on line 6 went through so stack 2 has 3
+        assert_eq!(report.problems[4].heights, vec![1, 3]);
+        assert_eq!(report.problems[4].crates_short(), 1);
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap},
+};
+
+use color_eyre::Result;
+
+use super::{group::ElfGroup, interval::Interval};
+
+/// One elf's range along with where it came from in the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Assignment {
+    pub(super) line_number: usize,
+    pub(super) interval: Interval,
+}
+
+/// A run of sections that are all covered by the same number of elves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct CoverageSegment {
+    pub(super) interval: Interval,
+    pub(super) elves: usize,
+}
+
+/// Looks at every assignment from every line at once instead of one pair at a time.
+///
+/// Everything is worked out with a sweep over the sorted range ends so it stays
+/// O(n log n) in the number of assignments.
+#[derive(Debug)]
+pub(super) struct GlobalAnalysis {
+    pub(super) assignments: Vec<Assignment>,
+    /// Covers the whole span from the lowest section to the highest in order, with gaps
+    /// showing up as segments with 0 elves
+    pub(super) segments: Vec<CoverageSegment>,
+}
+
+pub(super) fn parse_assignments<I>(input_lines: I) -> Result<Vec<Assignment>>
+where
+    I: Iterator<Item = String>,
+{
+    let mut assignments = Vec::new();
+    for (line, line_number) in input_lines.zip(1..) {
+        for interval in line.parse::<ElfGroup>()?.elves {
+            assignments.push(Assignment {
+                line_number,
+                interval,
+            });
+        }
+    }
+    Ok(assignments)
+}
+
+pub(super) fn analyze_assignments<I>(input_lines: I) -> Result<GlobalAnalysis>
+where
+    I: Iterator<Item = String>,
+{
+    Ok(GlobalAnalysis::new(parse_assignments(input_lines)?))
+}
+
+impl GlobalAnalysis {
+    pub(super) fn new(assignments: Vec<Assignment>) -> GlobalAnalysis {
+        // +1 when a range starts and -1 the section after it ends
+        let mut events = Vec::with_capacity(assignments.len() * 2);
+        for assignment in assignments.iter() {
+            events.push((assignment.interval.start, 1));
+            events.push((assignment.interval.end + 1, -1));
+        }
+        events.sort_unstable();
+
+        let mut segments: Vec<CoverageSegment> = Vec::new();
+        let mut elves: isize = 0;
+        let mut index = 0;
+        while index < events.len() {
+            let position = events[index].0;
+            while index < events.len() && events[index].0 == position {
+                elves += events[index].1;
+                index += 1;
+            }
+            if let Some((next_position, _)) = events.get(index) {
+                let interval = Interval {
+                    start: position,
+                    end: next_position - 1,
+                };
+                // Neighbouring runs with the same count are joined so each segment is as long
+                // as it can be
+                match segments.last_mut() {
+                    Some(last) if last.elves == elves as usize => last.interval.end = interval.end,
+                    _ => segments.push(CoverageSegment {
+                        interval,
+                        elves: elves as usize,
+                    }),
+                }
+            }
+        }
+
+        GlobalAnalysis {
+            assignments,
+            segments,
+        }
+    }
+
+    /// Lowest to highest section assigned to anyone
+    pub(super) fn span(&self) -> Option<Interval> {
+        Some(Interval {
+            start: self.segments.first()?.interval.start,
+            end: self.segments.last()?.interval.end,
+        })
+    }
+
+    /// Number of distinct sections at least one elf is working on
+    pub(super) fn sections_covered(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| segment.elves > 0)
+            .map(|segment| segment.interval.len())
+            .sum()
+    }
+
+    /// Ranges of sections that more than `elves` elves are assigned to
+    pub(super) fn covered_by_more_than(&self, elves: usize) -> Vec<Interval> {
+        let mut covered: Vec<Interval> = Vec::new();
+        for segment in self.segments.iter().filter(|segment| segment.elves > elves) {
+            match covered.last_mut() {
+                Some(last) if last.end + 1 == segment.interval.start => {
+                    last.end = segment.interval.end
+                }
+                _ => covered.push(segment.interval),
+            }
+        }
+        covered
+    }
+
+    /// Sections inside the span that nobody is assigned to
+    pub(super) fn gaps(&self) -> Vec<Interval> {
+        self.segments
+            .iter()
+            .filter(|segment| segment.elves == 0)
+            .map(|segment| segment.interval)
+            .collect()
+    }
+
+    /// Every pair of lines where an elf from one overlaps an elf from the other, smaller line
+    /// number first
+    pub(super) fn overlapping_line_pairs(&self) -> Vec<(usize, usize)> {
+        let mut by_start = self.assignments.clone();
+        by_start.sort_unstable_by_key(|assignment| assignment.interval.start);
+
+        // Ranges that started already, soonest to end on top
+        let mut active: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::new();
+        let mut pairs = BTreeSet::new();
+        for assignment in by_start {
+            while let Some(Reverse((end, _))) = active.peek() {
+                if *end < assignment.interval.start {
+                    active.pop();
+                } else {
+                    break;
+                }
+            }
+            // Whatever is left overlaps this range
+            for Reverse((_, line_number)) in active.iter() {
+                if *line_number != assignment.line_number {
+                    pairs.insert((
+                        assignment.line_number.min(*line_number),
+                        assignment.line_number.max(*line_number),
+                    ));
+                }
+            }
+            active.push(Reverse((assignment.interval.end, assignment.line_number)));
+        }
+        pairs.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(s: &str) -> Interval {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_global_analysis() {
+        let input = "1-2,5-6\n8-9,9-10\n2-5,11-12";
+        let analysis = analyze_assignments(input.split('\n').map(str::to_string)).unwrap();
+
+        assert_eq!(analysis.span(), Some(interval("1-12")));
+        assert_eq!(analysis.sections_covered(), 11);
+        assert_eq!(analysis.gaps(), vec![interval("7-7")]);
+        assert_eq!(
+            analysis.covered_by_more_than(1),
+            vec![interval("2-2"), interval("5-5"), interval("9-9")]
+        );
+        assert_eq!(
+            analysis.covered_by_more_than(0),
+            vec![interval("1-6"), interval("8-12")]
+        );
+        assert_eq!(analysis.overlapping_line_pairs(), vec![(1, 3)]);
+
+        // The last section there can be still has room for the end event after it
+        let highest = usize::MAX - 1;
+        let input = format!("{}-{},{}-{}", highest - 2, highest, highest, highest);
+        let analysis = analyze_assignments(std::iter::once(input)).unwrap();
+        assert_eq!(analysis.sections_covered(), 3);
+        assert_eq!(
+            analysis.covered_by_more_than(1),
+            vec![Interval {
+                start: highest,
+                end: highest
+            }]
+        );
+    }
+
+    #[test]
+    fn test_global_example() {
+        let input = "2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+        let analysis = analyze_assignments(input.split('\n').map(str::to_string)).unwrap();
+        assert_eq!(analysis.sections_covered(), 8);
+        assert!(analysis.gaps().is_empty());
+        // Every line shares at least one section with every other line
+        assert_eq!(analysis.overlapping_line_pairs().len(), 15);
+    }
+
+    #[test]
+    fn test_global_large() {
+        let mut next = crate::test_random_numbers(0x2022);
+        let mut lines = Vec::new();
+        for _ in 0..200_000 {
+            let first = next(1_000_000);
+            let second = next(1_000_000);
+            lines.push(format!(
+                "{}-{},{}-{}",
+                first,
+                first + next(20),
+                second,
+                second + next(20)
+            ));
+        }
+
+        let analysis = analyze_assignments(lines.iter().cloned()).unwrap();
+
+        let mut covered = vec![0_usize; 1_000_100];
+        for assignment in analysis.assignments.iter() {
+            for elves in &mut covered[assignment.interval.start..=assignment.interval.end] {
+                *elves += 1;
+            }
+        }
+        assert_eq!(
+            analysis.sections_covered(),
+            covered.iter().filter(|elves| **elves > 0).count()
+        );
+        assert_eq!(
+            analysis
+                .covered_by_more_than(2)
+                .iter()
+                .map(Interval::len)
+                .sum::<usize>(),
+            covered.iter().filter(|elves| **elves > 2).count()
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::str::FromStr;
+
+use color_eyre::{Report, Result};
+
+use super::interval::Interval;
+
+/// All of the elves on one line of the input, which doesn't have to be a pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ElfGroup {
+    pub(super) elves: Vec<Interval>,
+}
+
+impl ElfGroup {
+    /// Some elf's range covers every other elf's range
+    pub(super) fn has_range_containing_all(&self) -> bool {
+        let start = self.elves.iter().map(|elf| elf.start).min();
+        let end = self.elves.iter().map(|elf| elf.end).max();
+        match (start, end) {
+            (Some(start), Some(end)) => self
+                .elves
+                .iter()
+                .any(|elf| elf.start == start && elf.end == end),
+            _ => false,
+        }
+    }
+
+    /// The sections every elf in the group is assigned to
+    pub(super) fn common_sections(&self) -> Option<Interval> {
+        let mut elves = self.elves.iter();
+        let first = *elves.next()?;
+        elves.try_fold(first, |common, elf| common.intersection(elf))
+    }
+
+    /// How many pairs of elves in the group share at least one section
+    pub(super) fn overlapping_pairs(&self) -> usize {
+        let mut by_start = self.elves.clone();
+        by_start.sort_unstable();
+        let starts = by_start.iter().map(|elf| elf.start).collect::<Vec<_>>();
+
+        // Everything that starts after this elf but no later than it ends overlaps it
+        by_start
+            .iter()
+            .enumerate()
+            .map(|(index, elf)| starts.partition_point(|start| *start <= elf.end) - index - 1)
+            .sum()
+    }
+}
+
+impl FromStr for ElfGroup {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(Report::msg("no elves found on the line"));
+        }
+        Ok(ElfGroup {
+            elves: s
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<Interval>>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(s: &str) -> ElfGroup {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(group("2-4").elves.len(), 1);
+        assert_eq!(group("2-4,6-8,1-9").elves.len(), 3);
+        assert!("".parse::<ElfGroup>().is_err());
+        assert!("2-4,".parse::<ElfGroup>().is_err());
+    }
+
+    #[test]
+    fn test_group_queries() {
+        let elves = group("2-8,3-7,1-4");
+        assert!(!elves.has_range_containing_all());
+        assert_eq!(elves.common_sections(), "3-4".parse().ok());
+        assert_eq!(elves.overlapping_pairs(), 3);
+
+        let elves = group("1-9,3-7,1-4,8-8");
+        assert!(elves.has_range_containing_all());
+        assert_eq!(elves.common_sections(), None);
+        // 1-9 with everything, 3-7 with 1-4
+        assert_eq!(elves.overlapping_pairs(), 4);
+
+        let elves = group("5-5");
+        assert!(elves.has_range_containing_all());
+        assert_eq!(elves.overlapping_pairs(), 0);
+    }
+}
@@ -0,0 +1,183 @@
+use std::{fmt::Display, str::FromStr};
+
+use color_eyre::{Report, Result};
+
+/// The sections an elf is assigned to, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(super) struct Interval {
+    pub(super) start: usize,
+    pub(super) end: usize,
+}
+
+impl Interval {
+    /// `end` can't be `usize::MAX` so that `len` and the section after the end always fit
+    pub(super) fn new(start: usize, end: usize) -> Result<Interval> {
+        if end == usize::MAX {
+            return Err(Report::msg(format!("section {} is too high", end)));
+        }
+        if start > end {
+            return Err(Report::msg(format!(
+                "range {}-{} starts after it ends",
+                start, end
+            )));
+        }
+        Ok(Interval { start, end })
+    }
+
+    /// Number of sections, never 0 since both ends are included
+    pub(super) fn len(&self) -> usize {
+        self.end - self.start + 1
+    }
+
+    pub(super) fn contains_section(&self, section: usize) -> bool {
+        self.start <= section && section <= self.end
+    }
+
+    /// Every section of `other` is also in `self`
+    pub(super) fn contains(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub(super) fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub(super) fn intersection(&self, other: &Interval) -> Option<Interval> {
+        if self.overlaps(other) {
+            Some(Interval {
+                start: self.start.max(other.start),
+                end: self.end.min(other.end),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// One interval if they overlap or touch, otherwise both in order
+    pub(super) fn union(&self, other: &Interval) -> Vec<Interval> {
+        let (first, second) = if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        if second.start <= first.end.saturating_add(1) {
+            vec![Interval {
+                start: first.start,
+                end: first.end.max(second.end),
+            }]
+        } else {
+            vec![*first, *second]
+        }
+    }
+
+    /// The sections of `self` that aren't in `other`. Can be split in two if `other` is in
+    /// the middle.
+    pub(super) fn difference(&self, other: &Interval) -> Vec<Interval> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let mut pieces = Vec::with_capacity(2);
+        if self.start < other.start {
+            pieces.push(Interval {
+                start: self.start,
+                end: other.start - 1,
+            });
+        }
+        if other.end < self.end {
+            pieces.push(Interval {
+                start: other.end + 1,
+                end: self.end,
+            });
+        }
+        pieces
+    }
+}
+
+impl FromStr for Interval {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut iter = s.trim().split('-');
+        let start = iter
+            .next()
+            .ok_or_else(|| Report::msg("Failed to find low range"))?
+            .parse()?;
+        let end = iter
+            .next()
+            .ok_or_else(|| Report::msg("Failed to find high range"))?
+            .parse()?;
+        if iter.next().is_some() {
+            return Err(Report::msg(format!("too many parts in range {:?}", s)));
+        }
+        Interval::new(start, end)
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(s: &str) -> Interval {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(interval("2-4"), Interval { start: 2, end: 4 });
+        assert_eq!(interval("6-6").len(), 1);
+        assert!("4-2".parse::<Interval>().is_err());
+        assert!("4".parse::<Interval>().is_err());
+        assert!("1-2-3".parse::<Interval>().is_err());
+        assert!("a-2".parse::<Interval>().is_err());
+        assert!(format!("1-{}", usize::MAX).parse::<Interval>().is_err());
+        let highest = format!("{}-{}", usize::MAX - 1, usize::MAX - 1);
+        assert_eq!(interval(&highest).len(), 1);
+    }
+
+    #[test]
+    fn test_set_operations() {
+        assert!(interval("2-8").contains(&interval("3-7")));
+        assert!(!interval("3-7").contains(&interval("2-8")));
+        assert!(interval("5-7").overlaps(&interval("7-9")));
+        assert!(!interval("2-3").overlaps(&interval("4-5")));
+
+        assert_eq!(
+            interval("2-6").intersection(&interval("4-8")),
+            Some(interval("4-6"))
+        );
+        assert_eq!(interval("2-3").intersection(&interval("4-5")), None);
+
+        assert_eq!(
+            interval("4-8").union(&interval("2-6")),
+            vec![interval("2-8")]
+        );
+        assert_eq!(
+            interval("2-3").union(&interval("4-5")),
+            vec![interval("2-5")]
+        );
+        assert_eq!(
+            interval("6-8").union(&interval("2-4")),
+            vec![interval("2-4"), interval("6-8")]
+        );
+
+        assert_eq!(
+            interval("2-8").difference(&interval("4-5")),
+            vec![interval("2-3"), interval("6-8")]
+        );
+        assert_eq!(
+            interval("2-6").difference(&interval("4-8")),
+            vec![interval("2-3")]
+        );
+        assert_eq!(interval("3-7").difference(&interval("2-8")), vec![]);
+        assert_eq!(
+            interval("2-3").difference(&interval("5-8")),
+            vec![interval("2-3")]
+        );
+    }
+}
@@ -1,125 +1,39 @@
 #![allow(dead_code, unused)]
 use color_eyre::{Report, Result};
 
-struct Elf {
-    small: usize,
-    high: usize,
-}
+use self::{group::ElfGroup, interval::Interval};
 
-fn part_two_from_iter<I>(input_lines: I) -> Result<usize>
+mod global;
+mod group;
+mod interval;
+mod rebalance;
+
+fn count_groups<I, F>(input_lines: I, predicate: F) -> Result<usize>
 where
     I: Iterator<Item = String>,
+    F: Fn(&ElfGroup) -> bool,
 {
-    let mut overlap_count = 0;
-
-    for line in input_lines.map(|x| x.trim_end().trim_start().to_string()) {
-        let mut line_split = line.split(',');
-        match (line_split.next(), line_split.next(), line_split.next()) {
-            (Some(first_elf), Some(second_elf), None) => {
-                let mut iter = first_elf.split('-');
-                let first_elf = Elf {
-                    small: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find low range"))?
-                        .parse()?,
-                    high: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find high range"))?
-                        .parse()?,
-                };
-
-                let mut iter = second_elf.split('-');
-                let second_elf = Elf {
-                    small: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find low range"))?
-                        .parse()?,
-                    high: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find high range"))?
-                        .parse()?,
-                };
-
-                match first_elf.small.cmp(&second_elf.small) {
-                    std::cmp::Ordering::Less => {
-                        if first_elf.high >= second_elf.small {
-                            overlap_count += 1
-                        }
-                    }
-                    std::cmp::Ordering::Equal => overlap_count += 1, // This means that one has to be a subset of the other,
-                    std::cmp::Ordering::Greater => {
-                        if second_elf.high >= first_elf.small {
-                            overlap_count += 1;
-                        }
-                    }
-                }
-            }
-            _ => {
-                return Err(Report::msg(
-                    "the number of elves found was not equal to the required 2",
-                ))
-            }
+    let mut count = 0;
+    for line in input_lines {
+        if predicate(&line.parse()?) {
+            count += 1;
         }
     }
-    Ok(overlap_count)
+    Ok(count)
+}
+
+fn part_two_from_iter<I>(input_lines: I) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    count_groups(input_lines, |group| group.overlapping_pairs() > 0)
 }
 
 fn part_one_from_iter<I>(input_lines: I) -> Result<usize>
 where
     I: Iterator<Item = String>,
 {
-    let mut complete_overlap_count = 0;
-
-    for line in input_lines.map(|x| x.trim_end().trim_start().to_string()) {
-        let mut line_split = line.split(',');
-        match (line_split.next(), line_split.next(), line_split.next()) {
-            (Some(first_elf), Some(second_elf), None) => {
-                let mut iter = first_elf.split('-');
-                let first_elf = Elf {
-                    small: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find low range"))?
-                        .parse()?,
-                    high: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find high range"))?
-                        .parse()?,
-                };
-
-                let mut iter = second_elf.split('-');
-                let second_elf = Elf {
-                    small: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find low range"))?
-                        .parse()?,
-                    high: iter
-                        .next()
-                        .ok_or_else(|| Report::msg("Failed to find high range"))?
-                        .parse()?,
-                };
-
-                match first_elf.small.cmp(&second_elf.small) {
-                    std::cmp::Ordering::Less => {
-                        if first_elf.high >= second_elf.high {
-                            complete_overlap_count += 1
-                        }
-                    }
-                    std::cmp::Ordering::Equal => complete_overlap_count += 1, // This means that one has to be a subset of the other,
-                    std::cmp::Ordering::Greater => {
-                        if second_elf.high >= first_elf.high {
-                            complete_overlap_count += 1;
-                        }
-                    }
-                }
-            }
-            _ => {
-                return Err(Report::msg(
-                    "the number of elves found was not equal to the required 2",
-                ))
-            }
-        }
-    }
-    Ok(complete_overlap_count)
+    count_groups(input_lines, ElfGroup::has_range_containing_all)
 }
 
 #[cfg(test)]
@@ -158,6 +72,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_backwards_range() {
+        let input = "2-4,6-8\n4-2,6-8".split('\n').map(str::to_string);
+        assert!(part_one_from_iter(input).is_err());
+    }
+
+    #[test]
+    fn test_larger_groups() {
+        let input = "2-4,6-8,1-9\n2-3,4-5,6-7\n5-7,7-9,1-5"
+            .split('\n')
+            .map(str::to_string);
+        assert_eq!(part_one_from_iter(input).unwrap(), 1);
+
+        let input = "2-4,6-8,1-9\n2-3,4-5,6-7\n5-7,7-9,1-5"
+            .split('\n')
+            .map(str::to_string);
+        assert_eq!(part_two_from_iter(input).unwrap(), 2);
+    }
+
     #[test]
     fn test_part_two_example() {
         let input = "2-4,6-8
@@ -0,0 +1,320 @@
+use std::{cmp::Reverse, fmt::Display};
+
+use color_eyre::Result;
+
+use super::{group::ElfGroup, interval::Interval};
+
+/// A new assignment for one line of elves so no section is worked twice
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Rebalance {
+    pub(super) line_number: usize,
+    pub(super) before: Vec<Interval>,
+    /// An elf can end up with more than one range if another elf was in the middle of theirs,
+    /// or with none if they all had the same single section
+    pub(super) after: Vec<Vec<Interval>>,
+}
+
+impl Rebalance {
+    /// Sections each elf gives up
+    pub(super) fn sections_lost(&self) -> Vec<usize> {
+        self.before
+            .iter()
+            .zip(self.after.iter())
+            .map(|(before, after)| before.len() - after.iter().map(Interval::len).sum::<usize>())
+            .collect()
+    }
+}
+
+/// Joins with commas, except for an "and" before the last one
+fn join_with_and(items: &[String]) -> String {
+    match items.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} and {}", rest.join(", "), last),
+        Some((last, _)) => last.clone(),
+        None => String::new(),
+    }
+}
+
+impl Display for Rebalance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |ranges: &Vec<Interval>| {
+            if ranges.is_empty() {
+                "nothing".to_string()
+            } else {
+                ranges
+                    .iter()
+                    .map(Interval::to_string)
+                    .collect::<Vec<_>>()
+                    .join("+")
+            }
+        };
+        write!(
+            f,
+            "line {}: {} -> {} (giving up {})",
+            self.line_number,
+            self.before
+                .iter()
+                .map(Interval::to_string)
+                .collect::<Vec<_>>()
+                .join(","),
+            self.after
+                .iter()
+                .map(describe)
+                .collect::<Vec<_>>()
+                .join(","),
+            join_with_and(
+                &self
+                    .sections_lost()
+                    .iter()
+                    .map(usize::to_string)
+                    .collect::<Vec<_>>()
+            )
+        )
+    }
+}
+
+/// Splits up the overlaps in a group so each section is only worked once, or `None` if nobody
+/// overlaps.
+///
+/// The sections are cut into runs that are all worked by the same elves. A run worked by more
+/// than one elf is cut into equal pieces, one for each of them, so nobody loses much more than
+/// anyone else even when one range is inside another. The lowest piece goes to the elf whose
+/// range starts first. If the run doesn't split evenly the extra sections go to whoever has
+/// given up the most so far, then to the shorter ranges, then to the ranges that start later.
+pub(super) fn rebalance_group(elves: &[Interval]) -> Option<Vec<Vec<Interval>>> {
+    // Elves in the order they get pieces of a shared run
+    let mut order = (0..elves.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|elf| (elves[*elf].start, elves[*elf].end, *elf));
+
+    // Every place the set of elves working a section changes. Nothing ends on usize::MAX so
+    // the + 1 can't overflow.
+    let mut boundaries = elves
+        .iter()
+        .flat_map(|elf| [elf.start, elf.end + 1])
+        .collect::<Vec<usize>>();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let mut overlapped = false;
+    // Sections each elf has given up so far
+    let mut lost = vec![0; elves.len()];
+    let mut after: Vec<Vec<Interval>> = vec![Vec::new(); elves.len()];
+    let mut give = |elf: usize, start: usize, len: usize| {
+        if len == 0 {
+            return;
+        }
+        let end = start + len - 1;
+        match after[elf].last_mut() {
+            Some(last) if last.end + 1 == start => last.end = end,
+            _ => after[elf].push(Interval { start, end }),
+        }
+    };
+    for run in boundaries.windows(2) {
+        let (start, len) = (run[0], run[1] - run[0]);
+        let sharing = order
+            .iter()
+            .copied()
+            .filter(|elf| elves[*elf].contains_section(start))
+            .collect::<Vec<usize>>();
+        if sharing.len() > 1 {
+            overlapped = true;
+        }
+
+        let mut by_extra_first = sharing
+            .iter()
+            .enumerate()
+            .map(|(position, elf)| (Reverse(lost[*elf]), elves[*elf].len(), Reverse(position)))
+            .enumerate()
+            .collect::<Vec<_>>();
+        by_extra_first.sort_by_key(|(_, key)| *key);
+        let mut gets_extra = vec![false; sharing.len()];
+        for (position, _) in by_extra_first.iter().take(len % sharing.len().max(1)) {
+            gets_extra[*position] = true;
+        }
+
+        let mut next_start = start;
+        for (position, elf) in sharing.iter().enumerate() {
+            let piece = len / sharing.len() + usize::from(gets_extra[position]);
+            lost[*elf] += len - piece;
+            give(*elf, next_start, piece);
+            next_start += piece;
+        }
+    }
+
+    overlapped.then_some(after)
+}
+
+/// [`rebalance_group`] for just two elves
+pub(super) fn rebalance_pair(first: Interval, second: Interval) -> Option<[Vec<Interval>; 2]> {
+    let [first_after, second_after] =
+        <[Vec<Interval>; 2]>::try_from(rebalance_group(&[first, second])?)
+            .expect("one result for each elf");
+    Some([first_after, second_after])
+}
+
+#[derive(Debug, Default)]
+pub(super) struct RebalanceReport {
+    pub(super) suggestions: Vec<Rebalance>,
+    /// Extra times a section was being worked, so a section three elves had counts twice
+    pub(super) redundant_sections: usize,
+}
+
+/// Suggests a new assignment for every line where any of the elves overlap
+pub(super) fn suggest_rebalancing<I>(input_lines: I) -> Result<RebalanceReport>
+where
+    I: Iterator<Item = String>,
+{
+    let mut report = RebalanceReport::default();
+    for (line, line_number) in input_lines.zip(1..) {
+        let group = line.parse::<ElfGroup>()?;
+        if let Some(after) = rebalance_group(&group.elves) {
+            let suggestion = Rebalance {
+                line_number,
+                before: group.elves,
+                after,
+            };
+            report.redundant_sections += suggestion.sections_lost().iter().sum::<usize>();
+            report.suggestions.push(suggestion);
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(s: &str) -> Interval {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_rebalance_pair() {
+        assert_eq!(rebalance_pair(interval("2-4"), interval("6-8")), None);
+
+        // 4-6 overlaps, first is shorter so it keeps 2 of the 3
+        assert_eq!(
+            rebalance_pair(interval("2-6"), interval("4-9")),
+            Some([vec![interval("2-5")], vec![interval("6-9")]])
+        );
+        assert_eq!(
+            rebalance_pair(interval("4-9"), interval("2-6")),
+            Some([vec![interval("6-9")], vec![interval("2-5")]])
+        );
+        // 3-7 is inside 2-8, the outer elf keeps 3-4 and the inner one 5-7
+        assert_eq!(
+            rebalance_pair(interval("2-8"), interval("3-7")),
+            Some([
+                vec![interval("2-4"), interval("8-8")],
+                vec![interval("5-7")]
+            ])
+        );
+        assert_eq!(
+            rebalance_pair(interval("1-99"), interval("1-100")),
+            Some([vec![interval("1-50")], vec![interval("51-100")]])
+        );
+        assert_eq!(
+            rebalance_pair(interval("3-7"), interval("3-7")),
+            Some([vec![interval("3-4")], vec![interval("5-7")]])
+        );
+        assert_eq!(
+            rebalance_pair(interval("6-6"), interval("6-6")),
+            Some([vec![], vec![interval("6-6")]])
+        );
+    }
+
+    #[test]
+    fn test_rebalance_containment_is_even() {
+        for (outer, inner) in [("1-100", "1-99"), ("1-100", "2-100"), ("1-100", "40-60")] {
+            let [outer_after, inner_after] =
+                rebalance_pair(interval(outer), interval(inner)).unwrap();
+            let suggestion = Rebalance {
+                line_number: 1,
+                before: vec![interval(outer), interval(inner)],
+                after: vec![outer_after, inner_after],
+            };
+            let [outer_lost, inner_lost] = suggestion.sections_lost()[..] else {
+                unreachable!()
+            };
+            assert!(outer_lost.abs_diff(inner_lost) <= 1, "{}", suggestion);
+            assert_eq!(outer_lost + inner_lost, interval(inner).len());
+        }
+    }
+
+    #[test]
+    fn test_rebalance_group() {
+        // 3-4 is shared by all three, 2 by the first two and 5 by the last two
+        let elves = [interval("1-4"), interval("2-5"), interval("3-6")];
+        let after = rebalance_group(&elves).unwrap();
+        assert_eq!(
+            after,
+            vec![
+                vec![interval("1-1"), interval("3-3")],
+                vec![interval("2-2"), interval("5-5")],
+                vec![interval("4-4"), interval("6-6")]
+            ]
+        );
+        let suggestion = Rebalance {
+            line_number: 2,
+            before: elves.to_vec(),
+            after,
+        };
+        assert_eq!(
+            suggestion.to_string(),
+            "line 2: 1-4,2-5,3-6 -> 1-1+3-3,2-2+5-5,4-4+6-6 (giving up 2, 2 and 2)"
+        );
+
+        assert_eq!(
+            rebalance_group(&[interval("1-2"), interval("4-5"), interval("7-9")]),
+            None
+        );
+        assert_eq!(rebalance_group(&[interval("1-2")]), None);
+
+        let report = suggest_rebalancing(
+            ["1-4,2-5,3-6", "1-2", "1-2,3-4,5-6,6-9"]
+                .map(str::to_string)
+                .into_iter(),
+        )
+        .unwrap();
+        assert_eq!(report.suggestions.len(), 2);
+        assert_eq!(report.suggestions[1].line_number, 3);
+        // 2 and 5 twice each, 3 and 4 three times each, 6 twice
+        assert_eq!(report.redundant_sections, 1 + 1 + 2 + 2 + 1);
+    }
+
+    #[test]
+    fn test_suggest_rebalancing_example() {
+        let input = "2-4,6-8
+2-3,4-5
+5-7,7-9
+2-8,3-7
+6-6,4-6
+2-6,4-8";
+        let report = suggest_rebalancing(input.split('\n').map(str::to_string)).unwrap();
+        assert_eq!(report.suggestions.len(), 4);
+        assert_eq!(report.redundant_sections, 1 + 5 + 1 + 3);
+        for suggestion in report.suggestions.iter() {
+            let [first, second] = &suggestion.after[..] else {
+                unreachable!()
+            };
+            assert!(first.iter().all(|a| second.iter().all(|b| !a.overlaps(b))));
+            let overlap = suggestion.before[0]
+                .intersection(&suggestion.before[1])
+                .unwrap();
+            assert_eq!(
+                suggestion.sections_lost().iter().sum::<usize>(),
+                overlap.len()
+            );
+        }
+        assert_eq!(
+            report.suggestions[0].to_string(),
+            "line 3: 5-7,7-9 -> 5-6,7-9 (giving up 1 and 0)"
+        );
+    }
+
+    #[test]
+    fn test_suggest_rebalancing_my_input() {
+        let input = crate::read_file_line_by_line("src/day_four/input.txt").unwrap();
+        let report = suggest_rebalancing(input).unwrap();
+        assert_eq!(report.suggestions.len(), 801);
+    }
+}
@@ -0,0 +1,456 @@
+use std::{cell::OnceCell, collections::HashMap, fmt::Display};
+
+use color_eyre::{Report, Result};
+use regex::Regex;
+
+/// Index of a node in a [`FileSystem`], only meaningful for the file system that gave it out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(super) struct NodeId(usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum NodeKind {
+    File { size: usize },
+    Directory { children: Vec<NodeId> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Node {
+    pub(super) name: String,
+    /// Only the root doesn't have one
+    pub(super) parent: Option<NodeId>,
+    pub(super) kind: NodeKind,
+}
+
+/// Two `ls` outputs that don't agree about an entry. Line numbers count from 1 and
+/// `first_listed` is `None` when a directory was only seen through `cd`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum ListingConflict {
+    FileAndDirectory {
+        line_number: usize,
+        path: String,
+        first_listed: Option<usize>,
+    },
+    DifferentSizes {
+        line_number: usize,
+        path: String,
+        first_listed: usize,
+        first_size: usize,
+        size: usize,
+    },
+}
+
+impl Display for ListingConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListingConflict::FileAndDirectory {
+                line_number,
+                path,
+                first_listed: Some(first_listed),
+            } => write!(
+                f,
+                "line {}: {} is listed as both a file and a directory, see line {}",
+                line_number, path, first_listed
+            ),
+            ListingConflict::FileAndDirectory {
+                line_number,
+                path,
+                first_listed: None,
+            } => write!(
+                f,
+                "line {}: {} is listed as a file but was already used as a directory",
+                line_number, path
+            ),
+            ListingConflict::DifferentSizes {
+                line_number,
+                path,
+                first_listed,
+                first_size,
+                size,
+            } => write!(
+                f,
+                "line {}: {} is listed with size {} but was {} on line {}",
+                line_number, path, size, first_size, first_listed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ListingConflict {}
+
+/// Every file and directory lives in one `Vec` and points at the others by [`NodeId`].
+///
+/// A node can only be added under a directory that already exists, so a child always has a
+/// higher id than its parent. Going through the ids backwards is then a post-order walk, which
+/// is how the sizes are worked out without recursing.
+#[derive(Debug, Clone)]
+pub(super) struct FileSystem {
+    nodes: Vec<Node>,
+    /// Total size of everything under each node, worked out the first time it's needed
+    sizes: OnceCell<Vec<usize>>,
+}
+
+impl FileSystem {
+    pub(super) fn new() -> FileSystem {
+        FileSystem {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Directory {
+                    children: Vec::new(),
+                },
+            }],
+            sizes: OnceCell::new(),
+        }
+    }
+
+    pub(super) fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub(super) fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
+
+    pub(super) fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).parent
+    }
+
+    pub(super) fn is_directory(&self, id: NodeId) -> bool {
+        matches!(self.node(id).kind, NodeKind::Directory { .. })
+    }
+
+    pub(super) fn children(&self, id: NodeId) -> &[NodeId] {
+        match &self.node(id).kind {
+            NodeKind::Directory { children } => children,
+            NodeKind::File { .. } => &[],
+        }
+    }
+
+    pub(super) fn child(&self, directory: NodeId, name: &str) -> Option<NodeId> {
+        self.children(directory)
+            .iter()
+            .copied()
+            .find(|child| self.node(*child).name == name)
+    }
+
+    pub(super) fn directories(&self) -> impl Iterator<Item = NodeId> + '_ {
+        (0..self.nodes.len())
+            .map(NodeId)
+            .filter(|id| self.is_directory(*id))
+    }
+
+    /// Full path from the root, like `/a/e`
+    pub(super) fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.parent(current) {
+            names.push(self.node(current).name.as_str());
+            current = parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    fn add_node(&mut self, directory: NodeId, name: &str, kind: NodeKind) -> Result<NodeId> {
+        let id = NodeId(self.nodes.len());
+        match &mut self.nodes[directory.0].kind {
+            NodeKind::Directory { children } => children.push(id),
+            NodeKind::File { .. } => {
+                return Err(Report::msg(format!(
+                    "can't add {:?} inside the file {}",
+                    name,
+                    self.path(directory)
+                )))
+            }
+        }
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(directory),
+            kind,
+        });
+        self.sizes = OnceCell::new();
+        Ok(id)
+    }
+
+    /// Gives back the directory that's already there if it has been seen before
+    pub(super) fn add_directory(&mut self, directory: NodeId, name: &str) -> Result<NodeId> {
+        match self.child(directory, name) {
+            Some(existing) if self.is_directory(existing) => Ok(existing),
+            Some(existing) => Err(Report::msg(format!(
+                "{} is a file, not a directory",
+                self.path(existing)
+            ))),
+            None => self.add_node(
+                directory,
+                name,
+                NodeKind::Directory {
+                    children: Vec::new(),
+                },
+            ),
+        }
+    }
+
+    pub(super) fn add_file(
+        &mut self,
+        directory: NodeId,
+        name: &str,
+        size: usize,
+    ) -> Result<NodeId> {
+        self.add_node(directory, name, NodeKind::File { size })
+    }
+
+    /// Size of a file or everything inside a directory
+    pub(super) fn size(&self, id: NodeId) -> usize {
+        self.sizes.get_or_init(|| {
+            let mut sizes = vec![0; self.nodes.len()];
+            for (index, node) in self.nodes.iter().enumerate().rev() {
+                if let NodeKind::File { size } = node.kind {
+                    sizes[index] += size;
+                }
+                if let Some(parent) = node.parent {
+                    sizes[parent.0] += sizes[index];
+                }
+            }
+            sizes
+        })[id.0]
+    }
+
+    /// Works out the directory a `cd` ends up in, directories that haven't been listed yet are
+    /// created
+    pub(super) fn change_directory(&mut self, current: NodeId, path: &str) -> Result<NodeId> {
+        let mut current = if path.starts_with('/') {
+            self.root()
+        } else {
+            current
+        };
+        for name in path.split('/') {
+            current = match name {
+                "" | "." => current,
+                ".." => self.parent(current).unwrap_or(current),
+                name => self.add_directory(current, name)?,
+            };
+        }
+        Ok(current)
+    }
+
+    /// Builds the file system from the output of the `cd` and `ls` commands.
+    ///
+    /// Listing the same directory more than once is fine, entries are matched up by name. An
+    /// entry that doesn't match what was listed before is a [`ListingConflict`].
+    pub(super) fn from_transcript<I>(input: I) -> Result<FileSystem>
+    where
+        I: Iterator<Item = String>,
+    {
+        let cd_regex = Regex::new("^\\$ cd (.+)$")?;
+        let ls_regex = Regex::new("^\\$ ls$")?;
+        let file_regex = Regex::new("^([0-9]+) (.+)$")?;
+        let dir_regex = Regex::new("^dir (.+)$")?;
+
+        let mut file_system = FileSystem::new();
+        let mut current_directory = file_system.root();
+        // Where each entry was first listed so conflicts can point back to it
+        let mut listed_on: HashMap<NodeId, usize> = HashMap::new();
+        for (line, line_number) in input.zip(1..) {
+            let result = if let Some(captures) = cd_regex.captures(&line) {
+                file_system
+                    .change_directory(current_directory, &captures[1])
+                    .map(|directory| current_directory = directory)
+            } else if ls_regex.is_match(&line) {
+                Ok(())
+            } else if let Some(captures) = file_regex.captures(&line) {
+                match captures[1].parse::<usize>() {
+                    Err(err) => Err(err.into()),
+                    Ok(size) => match file_system.child(current_directory, &captures[2]) {
+                        Some(existing) => match file_system.node(existing).kind {
+                            NodeKind::File { size: first_size } if first_size == size => Ok(()),
+                            NodeKind::File { size: first_size } => {
+                                Err(ListingConflict::DifferentSizes {
+                                    line_number,
+                                    path: file_system.path(existing),
+                                    first_listed: listed_on[&existing],
+                                    first_size,
+                                    size,
+                                }
+                                .into())
+                            }
+                            NodeKind::Directory { .. } => Err(ListingConflict::FileAndDirectory {
+                                line_number,
+                                path: file_system.path(existing),
+                                first_listed: listed_on.get(&existing).copied(),
+                            }
+                            .into()),
+                        },
+                        None => file_system
+                            .add_file(current_directory, &captures[2], size)
+                            .map(|file| {
+                                listed_on.insert(file, line_number);
+                            }),
+                    },
+                }
+            } else if let Some(captures) = dir_regex.captures(&line) {
+                match file_system.child(current_directory, &captures[1]) {
+                    Some(existing) if !file_system.is_directory(existing) => {
+                        Err(ListingConflict::FileAndDirectory {
+                            line_number,
+                            path: file_system.path(existing),
+                            first_listed: listed_on.get(&existing).copied(),
+                        }
+                        .into())
+                    }
+                    _ => file_system
+                        .add_directory(current_directory, &captures[1])
+                        .map(|directory| {
+                            listed_on.entry(directory).or_insert(line_number);
+                        }),
+                }
+            } else {
+                Err(Report::msg(format!("didn't understand {:?}", line)))
+            };
+            // Conflicts already say which line they're on
+            result.map_err(|err| {
+                if err.is::<ListingConflict>() {
+                    err
+                } else {
+                    Report::msg(format!("line {}: {}", line_number, err))
+                }
+            })?;
+        }
+        Ok(file_system)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_and_sizes() {
+        let mut file_system = FileSystem::new();
+        let root = file_system.root();
+        let a = file_system.add_directory(root, "a").unwrap();
+        let e = file_system.add_directory(a, "e").unwrap();
+        file_system.add_file(e, "i", 584).unwrap();
+        file_system.add_file(a, "f", 29116).unwrap();
+        let b = file_system.add_file(root, "b.txt", 14848514).unwrap();
+
+        assert_eq!(file_system.add_directory(root, "a").unwrap(), a);
+        assert_eq!(file_system.path(e), "/a/e");
+        assert_eq!(file_system.path(root), "/");
+        assert_eq!(file_system.parent(e), Some(a));
+        assert_eq!(file_system.child(root, "b.txt"), Some(b));
+        assert!(!file_system.is_directory(b));
+        assert!(file_system.add_file(b, "nope", 1).is_err());
+
+        assert_eq!(file_system.size(e), 584);
+        assert_eq!(file_system.size(a), 584 + 29116);
+        assert_eq!(file_system.size(root), 584 + 29116 + 14848514);
+        // Adding something has to throw away the cached sizes
+        file_system.add_file(e, "j", 16).unwrap();
+        assert_eq!(file_system.size(a), 584 + 29116 + 16);
+        assert_eq!(file_system.directories().count(), 3);
+    }
+
+    #[test]
+    fn test_change_directory() {
+        let mut file_system = FileSystem::new();
+        let root = file_system.root();
+        let deep = file_system.change_directory(root, "a/b/c").unwrap();
+        assert_eq!(file_system.path(deep), "/a/b/c");
+        let up = file_system.change_directory(deep, "../../").unwrap();
+        assert_eq!(file_system.path(up), "/a");
+        assert_eq!(file_system.change_directory(deep, "/").unwrap(), root);
+        assert_eq!(file_system.change_directory(root, "..").unwrap(), root);
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let mut file_system = FileSystem::new();
+        let mut current = file_system.root();
+        for depth in 0..200_000 {
+            current = file_system.add_directory(current, "d").unwrap();
+            file_system.add_file(current, "f", depth % 10).unwrap();
+        }
+        let expected = (0..200_000).map(|depth| depth % 10).sum::<usize>();
+        assert_eq!(file_system.size(file_system.root()), expected);
+        assert_eq!(file_system.size(current), 9);
+    }
+
+    #[test]
+    fn test_listing_conflicts() {
+        let conflict = |lines: &[&str]| {
+            FileSystem::from_transcript(lines.iter().map(|line| line.to_string()))
+                .unwrap_err()
+                .downcast_ref::<ListingConflict>()
+                .cloned()
+        };
+        assert_eq!(
+            conflict(&["$ cd /", "$ ls", "dir a", "12 b", "$ ls", "dir a", "13 b"]),
+            Some(ListingConflict::DifferentSizes {
+                line_number: 7,
+                path: "/b".to_string(),
+                first_listed: 4,
+                first_size: 12,
+                size: 13,
+            })
+        );
+        assert_eq!(
+            conflict(&["$ ls", "dir a", "$ ls", "12 a"]),
+            Some(ListingConflict::FileAndDirectory {
+                line_number: 4,
+                path: "/a".to_string(),
+                first_listed: Some(2),
+            })
+        );
+        assert_eq!(
+            conflict(&["$ ls", "12 a", "$ ls", "dir a"]),
+            Some(ListingConflict::FileAndDirectory {
+                line_number: 4,
+                path: "/a".to_string(),
+                first_listed: Some(2),
+            })
+        );
+        let err = conflict(&["$ cd a", "$ cd ..", "$ ls", "12 a"]).unwrap();
+        assert_eq!(
+            err.to_string(),
+            "line 4: /a is listed as a file but was already used as a directory"
+        );
+
+        let input = ["$ ls", "12 a", "$ cd a"].map(str::to_string);
+        assert!(FileSystem::from_transcript(input.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_repeated_listing() {
+        let input = [
+            "$ cd /", "$ ls", "dir a", "12 b", "$ cd a", "$ ls", "5 c", "$ cd /", "$ ls", "12 b",
+            "dir a", "$ cd a", "$ ls", "5 c",
+        ];
+        let file_system =
+            FileSystem::from_transcript(input.into_iter().map(str::to_string)).unwrap();
+        assert_eq!(file_system.size(file_system.root()), 17);
+        assert_eq!(file_system.children(file_system.root()).len(), 2);
+    }
+
+    #[test]
+    fn test_from_transcript_errors() {
+        let input = ["$ cd /", "$ ls", "12 a", "what is this"].map(str::to_string);
+        let err = FileSystem::from_transcript(input.into_iter()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 4: didn't understand \"what is this\""
+        );
+
+        let input = ["$ cd /", "$ ls", "99999999999999999999999 a"].map(str::to_string);
+        let err = FileSystem::from_transcript(input.into_iter()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "line 3: number too large to fit in target type"
+        );
+
+        // Conflicts come back as themselves and aren't prefixed twice
+        let input = ["$ cd /", "$ ls", "12 a", "13 a"].map(str::to_string);
+        let err = FileSystem::from_transcript(input.into_iter()).unwrap_err();
+        assert!(err.is::<ListingConflict>());
+        assert!(err.to_string().starts_with("line 4: /a "), "{}", err);
+    }
+}
@@ -0,0 +1,146 @@
+use color_eyre::{Report, Result};
+
+/// The signal only uses lowercase letters
+pub(super) const ALPHABET_SIZE: usize = 26;
+
+/// Looks at a signal one character at a time and knows when the last `window` characters were
+/// all different. Each character is O(1) no matter how big the window is, since it only keeps a
+/// count for each letter and how many letters are in the window more than once.
+#[derive(Debug, Clone)]
+pub(super) struct MarkerDetector {
+    window: usize,
+    counts: [usize; ALPHABET_SIZE],
+    /// Letters with a count above 1
+    duplicates: usize,
+    /// The last `window` letters, `position % window` is the oldest once it's full
+    recent: Vec<u8>,
+    /// Characters seen so far
+    position: usize,
+}
+
+impl MarkerDetector {
+    pub(super) fn new(window: usize) -> Result<MarkerDetector> {
+        if window == 0 || window > ALPHABET_SIZE {
+            return Err(Report::msg(format!(
+                "window has to be between 1 and {} but was {}",
+                ALPHABET_SIZE, window
+            )));
+        }
+        Ok(MarkerDetector {
+            window,
+            counts: [0; ALPHABET_SIZE],
+            duplicates: 0,
+            recent: vec![0; window],
+            position: 0,
+        })
+    }
+
+    pub(super) fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Number of characters seen so far, which is the marker index when [`MarkerDetector::push`]
+    /// returns true
+    pub(super) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Adds the next character, true if it ends a marker
+    pub(super) fn push(&mut self, c_har: char) -> Result<bool> {
+        if !c_har.is_ascii() {
+            return Err(Report::msg(format!(
+                "unexpected {:?} at position {} in the communication stream",
+                c_har,
+                self.position + 1
+            )));
+        }
+        self.push_byte(c_har as u8)
+    }
+
+    /// Same as [`MarkerDetector::push`] for signals that are read as bytes
+    pub(super) fn push_byte(&mut self, byte: u8) -> Result<bool> {
+        if !byte.is_ascii_lowercase() {
+            return Err(Report::msg(format!(
+                "unexpected {:?} at position {} in the communication stream",
+                byte as char,
+                self.position + 1
+            )));
+        }
+        let letter = byte - b'a';
+
+        let slot = self.position % self.window;
+        if self.position >= self.window {
+            let oldest = self.recent[slot] as usize;
+            self.counts[oldest] -= 1;
+            if self.counts[oldest] == 1 {
+                self.duplicates -= 1;
+            }
+        }
+        self.recent[slot] = letter;
+        self.counts[letter as usize] += 1;
+        if self.counts[letter as usize] == 2 {
+            self.duplicates += 1;
+        }
+        self.position += 1;
+
+        Ok(self.position >= self.window && self.duplicates == 0)
+    }
+}
+
+/// Index just after the first run of `window` characters that are all different, counting from 1
+pub(super) fn find_marker(signal: &str, window: usize) -> Result<usize> {
+    // The signal is all ASCII so there's no need to decode chars
+    find_marker_bytes(signal.as_bytes(), window)
+}
+
+pub(super) fn find_marker_bytes(signal: &[u8], window: usize) -> Result<usize> {
+    let mut detector = MarkerDetector::new(window)?;
+    for byte in signal.iter() {
+        if detector.push_byte(*byte)? {
+            return Ok(detector.position());
+        }
+    }
+    if detector.position() < window {
+        Err(Report::msg("Not enough chars in the communication stream"))
+    } else {
+        Err(Report::msg("no marker index found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_marker() {
+        assert_eq!(find_marker("abcd", 1).unwrap(), 1);
+        assert_eq!(find_marker("aabcd", 2).unwrap(), 3);
+        assert_eq!(find_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4).unwrap(), 7);
+        assert_eq!(find_marker("abcdefghijklmnopqrstuvwxyz", 26).unwrap(), 26);
+        assert!(find_marker("abcabc", 4).is_err());
+        assert!(find_marker("abc", 4).is_err());
+        assert!(find_marker("abcd", 0).is_err());
+        assert!(find_marker("abcd", 27).is_err());
+        assert!(find_marker("abCd", 4).is_err());
+    }
+
+    #[test]
+    fn test_find_marker_matches_brute_force() {
+        let signal = crate::read_file_line_by_line("src/day_six/input.txt")
+            .unwrap()
+            .next()
+            .unwrap();
+        let chars = signal.chars().collect::<Vec<char>>();
+        for window in 1..=ALPHABET_SIZE {
+            let brute_force = chars
+                .windows(window)
+                .position(|run| {
+                    run.iter()
+                        .enumerate()
+                        .all(|(index, c_har)| !run[index + 1..].contains(c_har))
+                })
+                .map(|start| start + window);
+            assert_eq!(find_marker(&signal, window).ok(), brute_force, "{}", window);
+        }
+    }
+}
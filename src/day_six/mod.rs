@@ -1,50 +1,19 @@
 #![allow(dead_code)]
-use std::collections::{HashSet, VecDeque};
+use color_eyre::Result;
 
-use color_eyre::{Report, Result};
+use self::marker::find_marker;
+
+mod marker;
+mod parallel;
+mod protocol;
+mod stream;
 
 fn part_one(input_line: String) -> Result<usize> {
-    let mut chars_iter = input_line.chars();
-    let mut first_three: VecDeque<char> = (&mut chars_iter).take(3).collect();
-    if first_three.len() < 3 {
-        return Err(Report::msg("Not enough chars in the communication stream"));
-    }
-    for (c_har, marker_index) in chars_iter.zip(4..) {
-        first_three.push_back(c_har);
-        // I didn't want to allocate a HashSet for each check
-        if first_three[0] != first_three[1]
-            && first_three[0] != first_three[2]
-            && first_three[0] != first_three[3]
-            && first_three[1] != first_three[2]
-            && first_three[1] != first_three[3]
-            && first_three[2] != first_three[3]
-        {
-            return Ok(marker_index);
-        }
-        first_three.pop_front().unwrap();
-    }
-    Err(Report::msg("no marker index found"))
+    find_marker(&input_line, 4)
 }
 
 fn part_two(input_line: String) -> Result<usize> {
-    let mut chars_iter = input_line.chars();
-    let mut checking_chars: VecDeque<char> = (&mut chars_iter).take(13).collect();
-    if checking_chars.len() < 13 {
-        return Err(Report::msg("Not enough chars in the communication stream"));
-    }
-    let mut hash_set = HashSet::new();
-    for (c_har, marker_index) in chars_iter.zip(14..) {
-        checking_chars.push_back(c_har);
-        for c_har in checking_chars.iter() {
-            hash_set.insert(*c_har);
-        }
-        if hash_set.len() == 14 {
-            return Ok(marker_index);
-        }
-        checking_chars.pop_front().unwrap();
-        hash_set.clear();
-    }
-    Err(Report::msg("no marker index found"))
+    find_marker(&input_line, 14)
 }
 
 #[cfg(test)]
@@ -0,0 +1,153 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use color_eyre::{Report, Result};
+
+use super::marker::MarkerDetector;
+
+/// Chunks smaller than this aren't worth a thread
+const MIN_CHUNK_SIZE: usize = 64 * 1024;
+/// How often a chunk checks whether an earlier chunk already found something
+const CHECK_EVERY: usize = 4096;
+
+/// The first thing that decides the answer in part of the signal, positions count from 1 like
+/// the markers do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Found {
+    Marker(usize),
+    Invalid(usize),
+}
+
+impl Found {
+    fn position(&self) -> usize {
+        match self {
+            Found::Marker(position) | Found::Invalid(position) => *position,
+        }
+    }
+}
+
+/// Looks for markers that end in `signal[start..end]`. The detector starts `window - 1` bytes
+/// early so a marker that crosses into the chunk is still found. Gives up once `earliest` says
+/// another chunk found something before where this one has got to.
+fn search_chunk(
+    signal: &[u8],
+    window: usize,
+    start: usize,
+    end: usize,
+    earliest: &AtomicUsize,
+) -> Result<Option<Found>> {
+    let overlap_start = start.saturating_sub(window - 1);
+    let mut detector = MarkerDetector::new(window)?;
+    for (index, byte) in signal[overlap_start..end].iter().enumerate() {
+        let position = overlap_start + index + 1;
+        if index % CHECK_EVERY == 0 && earliest.load(Ordering::Relaxed) < position {
+            return Ok(None);
+        }
+        if !byte.is_ascii_lowercase() {
+            return Ok(Some(Found::Invalid(position)));
+        }
+        if detector.push_byte(*byte)? {
+            return Ok(Some(Found::Marker(position)));
+        }
+    }
+    Ok(None)
+}
+
+/// Same answer as [`super::marker::find_marker_bytes`] but the signal is split into chunks that
+/// are searched on `threads` threads at once
+pub(super) fn find_marker_parallel(signal: &[u8], window: usize, threads: usize) -> Result<usize> {
+    MarkerDetector::new(window)?;
+    let chunk_size = signal.len().div_ceil(threads.max(1)).max(MIN_CHUNK_SIZE);
+    let earliest = AtomicUsize::new(usize::MAX);
+
+    let found = thread::scope(|scope| {
+        let searches = (0..signal.len())
+            .step_by(chunk_size)
+            .map(|start| {
+                let end = (start + chunk_size).min(signal.len());
+                let earliest = &earliest;
+                scope.spawn(move || {
+                    let found = search_chunk(signal, window, start, end, earliest)?;
+                    if let Some(found) = found {
+                        earliest.fetch_min(found.position(), Ordering::Relaxed);
+                    }
+                    Ok(found)
+                })
+            })
+            .collect::<Vec<_>>();
+        searches
+            .into_iter()
+            .map(|search| {
+                search
+                    .join()
+                    .map_err(|_| Report::msg("a marker search thread panicked"))?
+            })
+            .collect::<Result<Vec<Option<Found>>>>()
+    })?;
+
+    // A bad byte and a marker can't be at the same position so the lowest position is the
+    // one the sequential search would have stopped at
+    match found.into_iter().flatten().min_by_key(Found::position) {
+        Some(Found::Marker(position)) => Ok(position),
+        Some(Found::Invalid(position)) => Err(Report::msg(format!(
+            "unexpected {:?} at position {} in the communication stream",
+            signal[position - 1] as char,
+            position
+        ))),
+        None if signal.len() < window => {
+            Err(Report::msg("Not enough chars in the communication stream"))
+        }
+        None => Err(Report::msg("no marker index found")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day_six::marker::find_marker_bytes;
+
+    fn same_answer(signal: &[u8], window: usize, threads: usize) {
+        let sequential = find_marker_bytes(signal, window).map_err(|err| err.to_string());
+        let parallel = find_marker_parallel(signal, window, threads).map_err(|err| err.to_string());
+        assert_eq!(
+            sequential, parallel,
+            "window {} threads {}",
+            window, threads
+        );
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let signal = crate::read_file_line_by_line("src/day_six/input.txt")
+            .unwrap()
+            .next()
+            .unwrap();
+        for window in [1, 4, 14, 26] {
+            same_answer(signal.as_bytes(), window, 4);
+        }
+
+        // Big enough for a lot of chunks, with the marker right across the edge of one
+        let mut signal = b"a".repeat(MIN_CHUNK_SIZE * 6);
+        let edge = MIN_CHUNK_SIZE * 3;
+        signal[edge - 2..edge + 2].copy_from_slice(b"wxyz");
+        for threads in [1, 2, 3, 8] {
+            same_answer(&signal, 4, threads);
+        }
+        // "awxy" is the first marker
+        assert_eq!(find_marker_parallel(&signal, 4, 6).unwrap(), edge + 1);
+
+        // A bad byte after the marker doesn't matter but one before it does
+        signal[edge + 10] = b'!';
+        same_answer(&signal, 4, 8);
+        signal[MIN_CHUNK_SIZE + 5] = b'!';
+        same_answer(&signal, 4, 8);
+        assert!(find_marker_parallel(&signal, 4, 8).is_err());
+
+        same_answer(&b"aab".repeat(MIN_CHUNK_SIZE), 4, 8);
+        same_answer(b"abc", 4, 8);
+        same_answer(b"", 4, 8);
+        assert!(find_marker_parallel(b"abcd", 0, 8).is_err());
+    }
+}
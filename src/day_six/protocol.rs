@@ -0,0 +1,238 @@
+use std::io::Read;
+
+use color_eyre::Result;
+
+use super::{marker::MarkerDetector, stream::ReadBytes};
+
+/// Window sizes for each layer of the protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Layers {
+    pub(super) packet_window: usize,
+    pub(super) message_window: usize,
+}
+
+impl Default for Layers {
+    /// The sizes from the puzzle
+    fn default() -> Self {
+        Layers {
+            packet_window: 4,
+            message_window: 14,
+        }
+    }
+}
+
+/// One message pulled out of the stream. Offsets are the number of bytes before that point, the
+/// same way the puzzle counts markers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Frame {
+    /// Just after the start-of-packet marker
+    pub(super) packet_marker: usize,
+    /// Just after the start-of-message marker, which is where the payload starts
+    pub(super) message_marker: usize,
+    /// Everything up to the next start-of-packet marker or the end of the stream
+    pub(super) payload: Vec<u8>,
+}
+
+impl Frame {
+    pub(super) fn payload_range(&self) -> std::ops::Range<usize> {
+        self.message_marker..self.message_marker + self.payload.len()
+    }
+}
+
+enum DecoderState {
+    SeekingPacket,
+    SeekingMessage { packet_marker: usize },
+    InPayload { frame: Frame },
+}
+
+/// Splits a stream into [`Frame`]s in one pass: a start-of-packet marker, then the next
+/// start-of-message marker, then the payload runs until the next start-of-packet marker.
+///
+/// Each layer starts a new [`MarkerDetector`] where the last one stopped, so a marker never
+/// uses bytes from the layer before it. A packet with no message before the stream ends doesn't
+/// give a frame.
+pub(super) struct Frames<I> {
+    bytes: I,
+    layers: Layers,
+    detector: MarkerDetector,
+    state: DecoderState,
+    position: usize,
+    finished: bool,
+}
+
+impl<I> Frames<I>
+where
+    I: Iterator<Item = Result<u8>>,
+{
+    pub(super) fn new(bytes: I, layers: Layers) -> Result<Frames<I>> {
+        // Make sure both windows are allowed before anything is read
+        MarkerDetector::new(layers.message_window)?;
+        Ok(Frames {
+            bytes,
+            layers,
+            detector: MarkerDetector::new(layers.packet_window)?,
+            state: DecoderState::SeekingPacket,
+            position: 0,
+            finished: false,
+        })
+    }
+
+    fn step(&mut self, byte: u8) -> Result<Option<Frame>> {
+        self.position += 1;
+        let found = self.detector.push_byte(byte)?;
+        match &mut self.state {
+            DecoderState::SeekingPacket => {
+                if found {
+                    self.state = DecoderState::SeekingMessage {
+                        packet_marker: self.position,
+                    };
+                    self.detector = MarkerDetector::new(self.layers.message_window)?;
+                }
+            }
+            DecoderState::SeekingMessage { packet_marker } => {
+                if found {
+                    self.state = DecoderState::InPayload {
+                        frame: Frame {
+                            packet_marker: *packet_marker,
+                            message_marker: self.position,
+                            payload: Vec::new(),
+                        },
+                    };
+                    self.detector = MarkerDetector::new(self.layers.packet_window)?;
+                }
+            }
+            DecoderState::InPayload { frame } => {
+                frame.payload.push(byte);
+                if found {
+                    // The marker belongs to the next packet
+                    let payload_len = frame.payload.len() - self.layers.packet_window;
+                    frame.payload.truncate(payload_len);
+                    let frame = std::mem::replace(
+                        &mut self.state,
+                        DecoderState::SeekingMessage {
+                            packet_marker: self.position,
+                        },
+                    );
+                    self.detector = MarkerDetector::new(self.layers.message_window)?;
+                    if let DecoderState::InPayload { frame } = frame {
+                        return Ok(Some(frame));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<I> Iterator for Frames<I>
+where
+    I: Iterator<Item = Result<u8>>,
+{
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        while let Some(byte) = self.bytes.next() {
+            let frame = byte.and_then(|byte| match byte {
+                b'\n' | b'\r' => Ok(None),
+                _ => self.step(byte).map(Some),
+            });
+            match frame {
+                Ok(Some(Some(frame))) => return Some(Ok(frame)),
+                Ok(Some(None)) => {}
+                Ok(None) => break,
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.finished = true;
+        match std::mem::replace(&mut self.state, DecoderState::SeekingPacket) {
+            DecoderState::InPayload { frame } => Some(Ok(frame)),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn decode_bytes<I>(
+    bytes: I,
+    layers: Layers,
+) -> Result<Frames<impl Iterator<Item = Result<u8>>>>
+where
+    I: IntoIterator<Item = u8>,
+{
+    Frames::new(bytes.into_iter().map(Ok), layers)
+}
+
+pub(super) fn decode_reader<R: Read>(reader: R, layers: Layers) -> Result<Frames<ReadBytes<R>>> {
+    Frames::new(ReadBytes::new(reader), layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_small_windows() {
+        let layers = Layers {
+            packet_window: 2,
+            message_window: 3,
+        };
+        let frames = decode_bytes(*b"aabaabczzzxyyyxywqq", layers)
+            .unwrap()
+            .collect::<Result<Vec<Frame>>>()
+            .unwrap();
+        assert_eq!(
+            frames,
+            vec![
+                Frame {
+                    packet_marker: 3,
+                    message_marker: 7,
+                    payload: b"zz".to_vec(),
+                },
+                Frame {
+                    packet_marker: 11,
+                    message_marker: 17,
+                    payload: b"qq".to_vec(),
+                },
+            ]
+        );
+        assert_eq!(frames[1].payload_range(), 17..19);
+
+        // No message after the last packet
+        let frames = decode_bytes(*b"aabaabczzzxyy", layers)
+            .unwrap()
+            .collect::<Result<Vec<Frame>>>()
+            .unwrap();
+        assert_eq!(frames.len(), 1);
+
+        let no_packets = Layers {
+            packet_window: 0,
+            ..Layers::default()
+        };
+        assert!(decode_bytes(*b"ab", no_packets).is_err());
+        let mut frames = decode_bytes(*b"abcA", layers).unwrap();
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+    }
+
+    #[test]
+    fn test_decode_my_input() {
+        let file = std::fs::File::open("src/day_six/input.txt").unwrap();
+        let frames = decode_reader(file, Layers::default())
+            .unwrap()
+            .collect::<Result<Vec<Frame>>>()
+            .unwrap();
+        assert_eq!(frames[0].packet_marker, 1920);
+        // The message search starts over after the packet marker, which is far enough back
+        // that it finds the same marker as part two
+        assert_eq!(frames[0].message_marker, 2334);
+        for pair in frames.windows(2) {
+            assert!(pair[0].payload_range().end + 4 == pair[1].packet_marker);
+            assert!(pair[1].message_marker - pair[1].packet_marker >= 14);
+        }
+    }
+}
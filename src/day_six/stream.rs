@@ -0,0 +1,241 @@
+use std::io::Read;
+
+use color_eyre::{Report, Result};
+
+use super::marker::MarkerDetector;
+
+/// How much is read from a [`Read`] at a time, the rest of the stream is never held in memory
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The bytes of a [`Read`], pulled in a chunk at a time
+pub(super) struct ReadBytes<R> {
+    reader: R,
+    chunk: Box<[u8]>,
+    filled: usize,
+    next: usize,
+}
+
+impl<R: Read> ReadBytes<R> {
+    pub(super) fn new(reader: R) -> ReadBytes<R> {
+        ReadBytes {
+            reader,
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            filled: 0,
+            next: 0,
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReadBytes<R> {
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == self.filled {
+            self.filled = loop {
+                match self.reader.read(&mut self.chunk) {
+                    Ok(filled) => break filled,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Some(Err(err.into())),
+                }
+            };
+            self.next = 0;
+            if self.filled == 0 {
+                return None;
+            }
+        }
+        self.next += 1;
+        Some(Ok(self.chunk[self.next - 1]))
+    }
+}
+
+/// Every marker in a stream of bytes in order, as the index just after it counting from 1.
+///
+/// A marker is any position where the last `window` characters are all different, so a long
+/// enough run of different letters gives a marker for each letter after the first `window`.
+/// The stream ends at the first newline so a file can be read as is.
+pub(super) struct Markers<I> {
+    bytes: I,
+    detector: MarkerDetector,
+    finished: bool,
+}
+
+impl<I> Markers<I>
+where
+    I: Iterator<Item = Result<u8>>,
+{
+    pub(super) fn new(bytes: I, window: usize) -> Result<Markers<I>> {
+        Ok(Markers {
+            bytes,
+            detector: MarkerDetector::new(window)?,
+            finished: false,
+        })
+    }
+
+    /// Number of bytes looked at so far
+    pub(super) fn position(&self) -> usize {
+        self.detector.position()
+    }
+}
+
+impl<I> Iterator for Markers<I>
+where
+    I: Iterator<Item = Result<u8>>,
+{
+    type Item = Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        for byte in self.bytes.by_ref() {
+            let found = byte.and_then(|byte| match byte {
+                b'\n' | b'\r' => Ok(None),
+                _ => Ok(Some(self.detector.push_byte(byte)?)),
+            });
+            match found {
+                Ok(Some(true)) => return Some(Ok(self.detector.position())),
+                Ok(Some(false)) => {}
+                Ok(None) => break,
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        self.finished = true;
+        None
+    }
+}
+
+/// Every marker in anything that gives bytes
+pub(super) fn markers_in_bytes<I>(
+    bytes: I,
+    window: usize,
+) -> Result<Markers<impl Iterator<Item = Result<u8>>>>
+where
+    I: IntoIterator<Item = u8>,
+{
+    Markers::new(bytes.into_iter().map(Ok), window)
+}
+
+/// Every marker in a [`Read`] without loading all of it
+pub(super) fn markers_in_reader<R: Read>(
+    reader: R,
+    window: usize,
+) -> Result<Markers<ReadBytes<R>>> {
+    Markers::new(ReadBytes::new(reader), window)
+}
+
+/// Streaming version of [`super::marker::find_marker`]
+pub(super) fn find_marker_in_reader<R: Read>(reader: R, window: usize) -> Result<usize> {
+    let mut markers = markers_in_reader(reader, window)?;
+    match markers.next() {
+        Some(marker) => marker,
+        None if markers.position() < window => {
+            Err(Report::msg("Not enough chars in the communication stream"))
+        }
+        None => Err(Report::msg("no marker index found")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use super::*;
+    use crate::day_six::marker::find_marker;
+
+    /// Repeats `pattern` until `length` bytes have been given and then gives `tail`, so huge
+    /// signals can be tested without ever being in memory
+    struct GeneratedSignal {
+        pattern: &'static [u8],
+        length: u64,
+        tail: &'static [u8],
+        given: u64,
+    }
+
+    impl Read for GeneratedSignal {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let total = self.length + self.tail.len() as u64;
+            let mut filled = 0;
+            while filled < buf.len() && self.given < total {
+                buf[filled] = if self.given < self.length {
+                    self.pattern[(self.given % self.pattern.len() as u64) as usize]
+                } else {
+                    self.tail[(self.given - self.length) as usize]
+                };
+                filled += 1;
+                self.given += 1;
+            }
+            Ok(filled)
+        }
+    }
+
+    fn generated(length: u64) -> GeneratedSignal {
+        GeneratedSignal {
+            // Every 4 in a row has two a's
+            pattern: b"aab",
+            length,
+            tail: b"bbbwxyz\n",
+            given: 0,
+        }
+    }
+
+    #[test]
+    fn test_find_marker_in_reader() {
+        let file = File::open("src/day_six/input.txt").unwrap();
+        assert_eq!(find_marker_in_reader(file, 4).unwrap(), 1920);
+        let file = File::open("src/day_six/input.txt").unwrap();
+        assert_eq!(find_marker_in_reader(file, 14).unwrap(), 2334);
+
+        assert!(find_marker_in_reader("abc\n".as_bytes(), 4).is_err());
+        assert!(find_marker_in_reader("abcabcabc".as_bytes(), 4).is_err());
+        assert!(find_marker_in_reader("abc1defg".as_bytes(), 4).is_err());
+
+        // Bigger than one chunk, the marker is in the tail
+        let length = (CHUNK_SIZE * 3 + 7) as u64;
+        assert_eq!(
+            find_marker_in_reader(generated(length), 4).unwrap() as u64,
+            length + 6
+        );
+    }
+
+    #[test]
+    fn test_all_markers() {
+        let markers = markers_in_bytes(*b"aabcdde", 3)
+            .unwrap()
+            .collect::<Result<Vec<usize>>>()
+            .unwrap();
+        assert_eq!(markers, vec![4, 5]);
+
+        let signal = crate::read_file_line_by_line("src/day_six/input.txt")
+            .unwrap()
+            .next()
+            .unwrap();
+        let mut markers = markers_in_bytes(signal.bytes(), 14).unwrap();
+        assert_eq!(
+            markers.next().unwrap().unwrap(),
+            find_marker(&signal, 14).unwrap()
+        );
+        assert!(markers.all(|marker| marker.is_ok()));
+
+        let mut markers = markers_in_bytes(*b"abcdA", 2).unwrap();
+        assert_eq!(markers.next().unwrap().unwrap(), 2);
+        assert_eq!(markers.nth(1).unwrap().unwrap(), 4);
+        assert!(markers.next().unwrap().is_err());
+        assert!(markers.next().is_none());
+    }
+
+    // cargo test --release stream_gigabytes -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn test_stream_gigabytes() {
+        let length = 4 * 1024 * 1024 * 1024;
+        let start = std::time::Instant::now();
+        assert_eq!(
+            find_marker_in_reader(generated(length), 4).unwrap() as u64,
+            length + 6
+        );
+        println!("4 GiB: {:?}", start.elapsed());
+    }
+}
@@ -0,0 +1,197 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use super::{priority::PriorityScheme, rucksack::Rucksack};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum RucksackStatus {
+    /// Exactly one item type is in both compartments, like the puzzle promises
+    Ok,
+    NoDuplicates,
+    MultipleDuplicates,
+    /// The line could not be read as a rucksack at all
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RucksackDiagnostic {
+    pub(super) line_number: usize,
+    /// Items in both compartments with their priorities
+    pub(super) duplicates: Vec<(char, usize)>,
+    pub(super) status: RucksackStatus,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct DiagnosticsReport {
+    pub(super) rucksacks: Vec<RucksackDiagnostic>,
+    /// How many times each item shows up across every rucksack that could be read
+    pub(super) item_frequencies: BTreeMap<char, usize>,
+}
+
+impl DiagnosticsReport {
+    /// The part one answer counting only the rucksacks that could be read
+    pub(super) fn priority_sum(&self) -> usize {
+        self.rucksacks
+            .iter()
+            .flat_map(|rucksack| rucksack.duplicates.iter())
+            .map(|(_, priority)| priority)
+            .sum()
+    }
+
+    /// Every rucksack that isn't [`RucksackStatus::Ok`]
+    pub(super) fn flagged(&self) -> impl Iterator<Item = &RucksackDiagnostic> {
+        self.rucksacks
+            .iter()
+            .filter(|rucksack| rucksack.status != RucksackStatus::Ok)
+    }
+}
+
+/// Checks every rucksack instead of stopping at the first bad one like part one does.
+/// Blank lines are skipped but still count towards line numbers.
+pub(super) fn diagnose<I>(input_lines: I, scheme: &PriorityScheme) -> DiagnosticsReport
+where
+    I: Iterator<Item = String>,
+{
+    let mut report = DiagnosticsReport::default();
+    for (line, line_number) in input_lines.zip(1..) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let rucksack = match Rucksack::parse_with(line, scheme) {
+            Ok(rucksack) => rucksack,
+            Err(err) => {
+                report.rucksacks.push(RucksackDiagnostic {
+                    line_number,
+                    duplicates: Vec::new(),
+                    status: RucksackStatus::Invalid(err.to_string()),
+                });
+                continue;
+            }
+        };
+
+        for item in line.chars() {
+            *report.item_frequencies.entry(item).or_default() += 1;
+        }
+
+        let duplicates = rucksack
+            .duplicates()
+            .items_with(scheme)
+            .into_iter()
+            .map(|item| {
+                (
+                    item,
+                    scheme
+                        .priority_of(item)
+                        .expect("the rucksack already parsed"),
+                )
+            })
+            .collect::<Vec<_>>();
+        let status = match duplicates.len() {
+            0 => RucksackStatus::NoDuplicates,
+            1 => RucksackStatus::Ok,
+            _ => RucksackStatus::MultipleDuplicates,
+        };
+        report.rucksacks.push(RucksackDiagnostic {
+            line_number,
+            duplicates,
+            status,
+        });
+    }
+    report
+}
+
+impl Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rucksack in self.rucksacks.iter() {
+            let duplicates = rucksack
+                .duplicates
+                .iter()
+                .map(|(item, priority)| format!("{}({})", item, priority))
+                .collect::<Vec<_>>()
+                .join(" ");
+            match &rucksack.status {
+                RucksackStatus::Ok => writeln!(f, "line {}: {}", rucksack.line_number, duplicates)?,
+                RucksackStatus::NoDuplicates => {
+                    writeln!(f, "line {}: no duplicates", rucksack.line_number)?
+                }
+                RucksackStatus::MultipleDuplicates => writeln!(
+                    f,
+                    "line {}: {} [multiple duplicates]",
+                    rucksack.line_number, duplicates
+                )?,
+                RucksackStatus::Invalid(err) => {
+                    writeln!(f, "line {}: invalid: {}", rucksack.line_number, err)?
+                }
+            }
+        }
+        writeln!(f, "item frequencies:")?;
+        for (item, count) in self.item_frequencies.iter() {
+            writeln!(f, "{} {}", item, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD: PriorityScheme = PriorityScheme::Standard;
+
+    #[test]
+    fn test_diagnose_example() {
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+        let report = diagnose(input.split('\n').map(str::to_string), &STANDARD);
+        assert_eq!(report.priority_sum(), 157);
+        assert_eq!(report.flagged().count(), 0);
+        assert_eq!(report.rucksacks[0].duplicates, vec![('p', 16)]);
+        assert_eq!(report.item_frequencies[&'v'], 6);
+    }
+
+    #[test]
+    fn test_diagnose_flags() {
+        let input = "abcabc\nabcdef\nabc\n\nabca";
+        let report = diagnose(input.split('\n').map(str::to_string), &STANDARD);
+        let flagged = report.flagged().collect::<Vec<_>>();
+
+        assert_eq!(flagged.len(), 3);
+        assert_eq!(flagged[0].status, RucksackStatus::MultipleDuplicates);
+        assert_eq!(flagged[0].duplicates, vec![('a', 1), ('b', 2), ('c', 3)]);
+        assert_eq!(flagged[1].status, RucksackStatus::NoDuplicates);
+        assert!(matches!(flagged[2].status, RucksackStatus::Invalid(_)));
+        assert_eq!(flagged[2].line_number, 3);
+        assert_eq!(report.rucksacks[3].line_number, 5);
+        assert_eq!(report.rucksacks[3].status, RucksackStatus::Ok);
+        // The odd line isn't counted
+        assert_eq!(report.item_frequencies[&'a'], 5);
+    }
+
+    #[test]
+    fn test_diagnose_my_input() {
+        let input = crate::read_file_line_by_line("src/day_three/input.txt").unwrap();
+        let report = diagnose(input, &STANDARD);
+        assert_eq!(report.priority_sum(), 8515);
+        assert_eq!(report.flagged().count(), 0);
+    }
+
+    #[test]
+    fn test_diagnose_custom_scheme() {
+        let scheme = "α 1\nβ 2\nγ 3".parse::<PriorityScheme>().unwrap();
+        let report = diagnose(
+            ["αββγ", "αγγγ", "ab"].map(str::to_string).into_iter(),
+            &scheme,
+        );
+        assert_eq!(report.rucksacks[0].duplicates, vec![('β', 2)]);
+        assert_eq!(report.rucksacks[1].duplicates, vec![('γ', 3)]);
+        assert!(matches!(
+            report.rucksacks[2].status,
+            RucksackStatus::Invalid(_)
+        ));
+        assert_eq!(report.priority_sum(), 5);
+    }
+}
@@ -0,0 +1,237 @@
+use std::fmt::Display;
+
+use color_eyre::{Report, Result};
+
+use super::{priority::PriorityScheme, rucksack::ItemSet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum BadgeError {
+    InvalidGroupSize,
+    /// Groups are numbered from 1
+    NoBadge {
+        group: usize,
+    },
+    MultipleBadges {
+        group: usize,
+        candidates: Vec<char>,
+    },
+    IncompleteGroup {
+        group: usize,
+        found: usize,
+        expected: usize,
+    },
+}
+
+impl Display for BadgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BadgeError::InvalidGroupSize => write!(f, "group size has to be at least 1"),
+            BadgeError::NoBadge { group } => write!(f, "Failed to find badge in group {}", group),
+            BadgeError::MultipleBadges { group, candidates } => write!(
+                f,
+                "More than one badge type found in group {}: {}",
+                group,
+                candidates.iter().collect::<String>()
+            ),
+            BadgeError::IncompleteGroup {
+                group,
+                found,
+                expected,
+            } => write!(
+                f,
+                "group {} only has {} of {} rucksacks",
+                group, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BadgeError {}
+
+/// Splits the rucksacks into groups of `group_size` and returns the items each group holds.
+/// Blank lines are skipped, and a group left short at the end of the input is an error.
+pub(super) fn groups<I>(
+    input_lines: I,
+    group_size: usize,
+    scheme: &PriorityScheme,
+) -> Result<Vec<Vec<ItemSet>>>
+where
+    I: Iterator<Item = String>,
+{
+    if group_size == 0 {
+        return Err(BadgeError::InvalidGroupSize.into());
+    }
+    let mut groups = Vec::new();
+    let mut current = Vec::with_capacity(group_size);
+    for line in input_lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        current.push(ItemSet::from_items_with(line, scheme)?);
+        if current.len() == group_size {
+            groups.push(std::mem::replace(
+                &mut current,
+                Vec::with_capacity(group_size),
+            ));
+        }
+    }
+    if !current.is_empty() {
+        return Err(BadgeError::IncompleteGroup {
+            group: groups.len() + 1,
+            found: current.len(),
+            expected: group_size,
+        }
+        .into());
+    }
+    Ok(groups)
+}
+
+/// The one item carried by every rucksack in each group
+pub(super) fn group_badges<I>(
+    input_lines: I,
+    group_size: usize,
+    scheme: &PriorityScheme,
+) -> Result<Vec<char>>
+where
+    I: Iterator<Item = String>,
+{
+    let mut badges = Vec::new();
+    for (rucksacks, group) in groups(input_lines, group_size, scheme)?
+        .into_iter()
+        .zip(1..)
+    {
+        let common = rucksacks
+            .into_iter()
+            .reduce(ItemSet::intersection)
+            .unwrap_or_default();
+        let mut candidates = common.items_with(scheme);
+        match candidates.len() {
+            0 => return Err(BadgeError::NoBadge { group }.into()),
+            1 => badges.push(candidates.remove(0)),
+            _ => return Err(BadgeError::MultipleBadges { group, candidates }.into()),
+        }
+    }
+    Ok(badges)
+}
+
+pub(super) fn part_two_with_group_size<I>(
+    input_lines: I,
+    group_size: usize,
+    scheme: &PriorityScheme,
+) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    let mut running_sum = 0;
+    for badge in group_badges(input_lines, group_size, scheme)? {
+        running_sum += scheme.priority_of(badge)?;
+    }
+    Ok(running_sum)
+}
+
+/// For each group, the items that show up in at least `min_shared` of its rucksacks
+pub(super) fn shared_by_at_least<I>(
+    input_lines: I,
+    group_size: usize,
+    min_shared: usize,
+    scheme: &PriorityScheme,
+) -> Result<Vec<ItemSet>>
+where
+    I: Iterator<Item = String>,
+{
+    if min_shared > group_size {
+        return Err(Report::msg(format!(
+            "no item can be shared by {} rucksacks in a group of {}",
+            min_shared, group_size
+        )));
+    }
+    let mut shared = Vec::new();
+    for rucksacks in groups(input_lines, group_size, scheme)? {
+        let mut counts = [0; 64];
+        for rucksack in rucksacks {
+            for priority in rucksack.priorities() {
+                counts[priority] += 1;
+            }
+        }
+        shared.push(ItemSet::from_priorities(
+            (0..counts.len()).filter(|priority| counts[*priority] >= min_shared.max(1)),
+        ));
+    }
+    Ok(shared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD: PriorityScheme = PriorityScheme::Standard;
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw";
+
+    #[test]
+    fn test_group_badges() {
+        let badges = group_badges(EXAMPLE.split('\n').map(str::to_string), 3, &STANDARD).unwrap();
+        assert_eq!(badges, vec!['r', 'Z']);
+
+        let result =
+            part_two_with_group_size(EXAMPLE.split('\n').map(str::to_string), 6, &STANDARD);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_incomplete_group() {
+        let err = group_badges(EXAMPLE.split('\n').map(str::to_string), 4, &STANDARD).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BadgeError>(),
+            Some(&BadgeError::IncompleteGroup {
+                group: 2,
+                found: 2,
+                expected: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_multiple_badges() {
+        let err = group_badges(
+            "abc\ncbd\nxcb".split('\n').map(str::to_string),
+            3,
+            &STANDARD,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BadgeError>(),
+            Some(&BadgeError::MultipleBadges {
+                group: 1,
+                candidates: vec!['b', 'c']
+            })
+        );
+    }
+
+    #[test]
+    fn test_shared_by_at_least() {
+        let shared = shared_by_at_least(
+            "abc\ncbd\nxyz".split('\n').map(str::to_string),
+            3,
+            2,
+            &STANDARD,
+        )
+        .unwrap();
+        assert_eq!(shared, vec![ItemSet::from_items("bc").unwrap()]);
+
+        let shared = shared_by_at_least(
+            "abc\ncbd\nxyz".split('\n').map(str::to_string),
+            3,
+            1,
+            &STANDARD,
+        )
+        .unwrap();
+        assert_eq!(shared, vec![ItemSet::from_items("abcdxyz").unwrap()]);
+    }
+}
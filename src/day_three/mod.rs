@@ -3,7 +3,55 @@ use std::collections::HashSet;
 
 use color_eyre::{Report, Result};
 
-fn part_two_from_iter<I>(mut input_lines: I) -> Result<usize>
+use self::{
+    groups::part_two_with_group_size,
+    priority::PriorityScheme,
+    rucksack::{ItemSet, Rucksack},
+};
+
+mod diagnostics;
+mod groups;
+mod priority;
+mod reorganize;
+mod rucksack;
+
+fn part_two_from_iter<I>(input_lines: I) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    part_two_with_scheme(input_lines, &PriorityScheme::Standard)
+}
+
+fn part_two_with_scheme<I>(input_lines: I, scheme: &PriorityScheme) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    part_two_with_group_size(input_lines, 3, scheme)
+}
+
+fn part_one_from_iter<I>(input_lines: I) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    part_one_with_scheme(input_lines, &PriorityScheme::Standard)
+}
+
+fn part_one_with_scheme<I>(input_lines: I, scheme: &PriorityScheme) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    let mut sum_priority = 0;
+    for line in input_lines {
+        sum_priority += Rucksack::parse_with(&line, scheme)?
+            .duplicates()
+            .priority_sum();
+    }
+    Ok(sum_priority)
+}
+
+// The original HashSet versions are kept around to benchmark against
+
+fn part_two_hash_set<I>(mut input_lines: I) -> Result<usize>
 where
     I: Iterator<Item = String>,
 {
@@ -58,7 +106,7 @@ where
     }
     Ok(running_sum)
 }
-fn part_one_from_iter<I>(input_lines: I) -> Result<usize>
+fn part_one_hash_set<I>(input_lines: I) -> Result<usize>
 where
     I: Iterator<Item = String>,
 {
@@ -94,19 +142,34 @@ where
     Ok(sum_priority)
 }
 
+// The standard priorities, see PriorityScheme for anything else
 fn priority_of(item: char) -> Result<usize> {
-    if !item.is_ascii() {
-        Err(Report::msg("Items are expected to be ascii"))
-    } else if item.is_ascii_lowercase() {
-        Ok(item as usize - 96)
-    } else {
-        Ok(item as usize - 38)
+    match item {
+        'a'..='z' => Ok(item as usize - 'a' as usize + 1),
+        'A'..='Z' => Ok(item as usize - 'A' as usize + 27),
+        _ => Err(Report::msg(format!(
+            "{:?} is not an item, items are expected to be a-z or A-Z",
+            item
+        ))),
+    }
+}
+
+fn item_of(priority: usize) -> Option<char> {
+    match priority {
+        1..=26 => Some((b'a' + priority as u8 - 1) as char),
+        27..=52 => Some((b'A' + priority as u8 - 27) as char),
+        _ => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{part_one_from_iter, part_two_from_iter, priority_of};
+    use std::time::Instant;
+
+    use super::{
+        item_of, part_one_from_iter, part_one_hash_set, part_one_with_scheme, part_two_from_iter,
+        part_two_hash_set, part_two_with_scheme, priority_of, PriorityScheme,
+    };
 
     #[test]
     fn test_priority_of() {
@@ -114,6 +177,18 @@ mod tests {
         assert_eq!(priority_of('z').unwrap(), 26);
         assert_eq!(priority_of('A').unwrap(), 27);
         assert_eq!(priority_of('Z').unwrap(), 52);
+        assert!(priority_of('1').is_err());
+        assert!(priority_of('!').is_err());
+        assert!(priority_of('é').is_err());
+    }
+
+    #[test]
+    fn test_item_of() {
+        for item in ('a'..='z').chain('A'..='Z') {
+            assert_eq!(item_of(priority_of(item).unwrap()), Some(item));
+        }
+        assert_eq!(item_of(0), None);
+        assert_eq!(item_of(53), None);
     }
 
     #[test]
@@ -196,4 +271,58 @@ mod tests {
             Err(err) => panic!("failed with err {:?}", err),
         }
     }
+
+    #[test]
+    fn test_custom_scheme() {
+        // Swapping the cases around
+        let mapping = ('A'..='Z')
+            .chain('a'..='z')
+            .zip(1..)
+            .map(|(item, priority)| format!("{} {}", item, priority))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let scheme = mapping.parse::<PriorityScheme>().unwrap();
+
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp
+        jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+        PmmdzqPrVvPwwTWBwg";
+        let result = part_one_with_scheme(input.split('\n').map(|x| x.to_string()), &scheme);
+        // p, L and P
+        assert_eq!(result.unwrap(), 42 + 12 + 16);
+
+        let result = part_two_with_scheme(input.split('\n').map(|x| x.to_string()), &scheme);
+        assert_eq!(result.unwrap(), 44);
+    }
+
+    // cargo test --release bench_rucksack -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_rucksack_vs_hash_set() {
+        let input = crate::read_file_line_by_line("src/day_three/input.txt")
+            .unwrap()
+            .collect::<Vec<_>>();
+        // 300 lines is a multiple of 3 so the groups still line up
+        let big_input = input
+            .iter()
+            .cycle()
+            .take(input.len() * 2000)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        let hash_set_result = part_one_hash_set(big_input.iter().cloned()).unwrap();
+        println!("part one HashSet: {:?}", start.elapsed());
+        let start = Instant::now();
+        let rucksack_result = part_one_from_iter(big_input.iter().cloned()).unwrap();
+        println!("part one Rucksack: {:?}", start.elapsed());
+        assert_eq!(hash_set_result, rucksack_result);
+
+        let start = Instant::now();
+        let hash_set_result = part_two_hash_set(big_input.iter().cloned()).unwrap();
+        println!("part two HashSet: {:?}", start.elapsed());
+        let start = Instant::now();
+        let rucksack_result = part_two_from_iter(big_input.iter().cloned()).unwrap();
+        println!("part two Rucksack: {:?}", start.elapsed());
+        assert_eq!(hash_set_result, rucksack_result);
+    }
 }
@@ -0,0 +1,133 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use color_eyre::{Report, Result};
+
+/// [`super::rucksack::ItemSet`] keeps items in a u64 so that's as high as a priority can go
+pub(super) const MAX_PRIORITY: usize = 63;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum PriorityScheme {
+    /// a-z are 1 through 26 and A-Z are 27 through 52
+    Standard,
+    Custom {
+        priorities: HashMap<char, usize>,
+        items: HashMap<usize, char>,
+    },
+}
+
+impl PriorityScheme {
+    pub(super) fn from_file<P>(path: P) -> Result<PriorityScheme>
+    where
+        P: AsRef<Path>,
+    {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub(super) fn priority_of(&self, item: char) -> Result<usize> {
+        match self {
+            PriorityScheme::Standard => super::priority_of(item),
+            PriorityScheme::Custom { priorities, .. } => priorities
+                .get(&item)
+                .copied()
+                .ok_or_else(|| Report::msg(format!("{:?} has no priority in this scheme", item))),
+        }
+    }
+
+    pub(super) fn item_of(&self, priority: usize) -> Option<char> {
+        match self {
+            PriorityScheme::Standard => super::item_of(priority),
+            PriorityScheme::Custom { items, .. } => items.get(&priority).copied(),
+        }
+    }
+}
+
+/// One `<item> <priority>` pair per line. Blank lines and lines starting with # are skipped.
+/// Items can be any single character, and every item and priority can only be used once.
+impl FromStr for PriorityScheme {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut priorities = HashMap::new();
+        let mut items = HashMap::new();
+        for (line, line_number) in s.lines().zip(1..) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut split = line.split_whitespace();
+            let (item, priority) = match (split.next(), split.next(), split.next()) {
+                (Some(item), Some(priority), None) => (item, priority),
+                _ => {
+                    return Err(Report::msg(format!(
+                        "line {}: expected `<item> <priority>` got {:?}",
+                        line_number, line
+                    )))
+                }
+            };
+            let mut item_chars = item.chars();
+            let item = match (item_chars.next(), item_chars.next()) {
+                (Some(item), None) => item,
+                _ => {
+                    return Err(Report::msg(format!(
+                        "line {}: items are a single character, got {:?}",
+                        line_number, item
+                    )))
+                }
+            };
+            let priority = priority
+                .parse::<usize>()
+                .map_err(|err| Report::msg(format!("line {}: {}", line_number, err)))?;
+            if !(1..=MAX_PRIORITY).contains(&priority) {
+                return Err(Report::msg(format!(
+                    "line {}: priority {} is outside of 1 to {}",
+                    line_number, priority, MAX_PRIORITY
+                )));
+            }
+            if priorities.insert(item, priority).is_some() {
+                return Err(Report::msg(format!(
+                    "line {}: {:?} was given a priority twice",
+                    line_number, item
+                )));
+            }
+            if let Some(other) = items.insert(priority, item) {
+                return Err(Report::msg(format!(
+                    "line {}: priority {} is already used by {:?}",
+                    line_number, priority, other
+                )));
+            }
+        }
+        Ok(PriorityScheme::Custom { priorities, items })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_scheme() {
+        let scheme = PriorityScheme::Standard;
+        assert_eq!(scheme.priority_of('p').unwrap(), 16);
+        assert_eq!(scheme.priority_of('L').unwrap(), 38);
+        assert!(scheme.priority_of('1').is_err());
+        assert_eq!(scheme.item_of(52), Some('Z'));
+    }
+
+    #[test]
+    fn test_custom_scheme() {
+        let scheme = "# Greek
+α 1
+β 2
+Ω 63
+"
+        .parse::<PriorityScheme>()
+        .unwrap();
+        assert_eq!(scheme.priority_of('β').unwrap(), 2);
+        assert_eq!(scheme.item_of(63), Some('Ω'));
+        assert!(scheme.priority_of('a').is_err());
+
+        assert!("a 64".parse::<PriorityScheme>().is_err());
+        assert!("a 1\nb 1".parse::<PriorityScheme>().is_err());
+        assert!("ab 1".parse::<PriorityScheme>().is_err());
+    }
+}
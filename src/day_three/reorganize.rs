@@ -0,0 +1,189 @@
+use std::fmt::Display;
+
+use color_eyre::{Report, Result};
+
+use super::{
+    priority::PriorityScheme,
+    rucksack::{split_compartments, Rucksack},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Compartment {
+    Left,
+    Right,
+}
+
+impl Display for Compartment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compartment::Left => write!(f, "left"),
+            Compartment::Right => write!(f, "right"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ItemMove {
+    pub(super) item: char,
+    pub(super) count: usize,
+    pub(super) from: Compartment,
+    pub(super) to: Compartment,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ReorganizationPlan {
+    pub(super) line_number: usize,
+    pub(super) moves: Vec<ItemMove>,
+    pub(super) left: String,
+    pub(super) right: String,
+}
+
+impl ReorganizationPlan {
+    /// Total number of single items that get moved
+    pub(super) fn items_moved(&self) -> usize {
+        self.moves.iter().map(|item_move| item_move.count).sum()
+    }
+}
+
+impl Display for ReorganizationPlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}:", self.line_number)?;
+        if self.moves.is_empty() {
+            write!(f, " nothing to move")?;
+        }
+        for item_move in self.moves.iter() {
+            write!(
+                f,
+                " move {} {} from {} to {};",
+                item_move.count, item_move.item, item_move.from, item_move.to
+            )?;
+        }
+        write!(f, " {} | {}", self.left, self.right)
+    }
+}
+
+/// Works out the fewest single item moves that leave no item type in both compartments.
+///
+/// Every duplicated type is handled on its own: whichever compartment has fewer of it gives
+/// them all up. When both have the same number the items go towards the smaller compartment so
+/// the sizes drift apart as little as possible. Moved items are added to the end of the
+/// compartment they land in and everything else keeps its order.
+pub(super) fn plan_rucksack(
+    line: &str,
+    line_number: usize,
+    scheme: &PriorityScheme,
+) -> Result<ReorganizationPlan> {
+    let line = line.trim();
+    let duplicates = Rucksack::parse_with(line, scheme)
+        .map_err(|err| Report::msg(format!("line {}: {}", line_number, err)))?
+        .duplicates();
+    let (left, right) = split_compartments(line)?;
+    let mut left = left.to_string();
+    let mut right = right.to_string();
+    let mut moves = Vec::new();
+
+    for item in duplicates.items_with(scheme) {
+        let left_count = left.chars().filter(|c| *c == item).count();
+        let right_count = right.chars().filter(|c| *c == item).count();
+        let from = match left_count.cmp(&right_count) {
+            std::cmp::Ordering::Less => Compartment::Left,
+            std::cmp::Ordering::Greater => Compartment::Right,
+            std::cmp::Ordering::Equal => {
+                if left.chars().count() > right.chars().count() {
+                    Compartment::Left
+                } else {
+                    Compartment::Right
+                }
+            }
+        };
+        let (source, destination, count, to) = match from {
+            Compartment::Left => (&mut left, &mut right, left_count, Compartment::Right),
+            Compartment::Right => (&mut right, &mut left, right_count, Compartment::Left),
+        };
+        source.retain(|c| c != item);
+        destination.extend(std::iter::repeat_n(item, count));
+        moves.push(ItemMove {
+            item,
+            count,
+            from,
+            to,
+        });
+    }
+
+    Ok(ReorganizationPlan {
+        line_number,
+        moves,
+        left,
+        right,
+    })
+}
+
+pub(super) fn plan_reorganization<I>(
+    input_lines: I,
+    scheme: &PriorityScheme,
+) -> Result<Vec<ReorganizationPlan>>
+where
+    I: Iterator<Item = String>,
+{
+    let mut plans = Vec::new();
+    for (line, line_number) in input_lines.zip(1..) {
+        if !line.trim().is_empty() {
+            plans.push(plan_rucksack(&line, line_number, scheme)?);
+        }
+    }
+    Ok(plans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rucksack::ItemSet;
+    use super::*;
+
+    const STANDARD: PriorityScheme = PriorityScheme::Standard;
+
+    #[test]
+    fn test_plan_example() {
+        let plan = plan_rucksack("vJrwpWtwJgWrhcsFMMfFFhFp", 1, &STANDARD).unwrap();
+        assert_eq!(
+            plan.moves,
+            vec![ItemMove {
+                item: 'p',
+                count: 1,
+                from: Compartment::Right,
+                to: Compartment::Left,
+            }]
+        );
+        assert_eq!(plan.left, "vJrwpWtwJgWrp");
+        assert_eq!(plan.right, "hcsFMMfFFhF");
+    }
+
+    #[test]
+    fn test_plan_picks_smaller_side() {
+        // a: 1 left 2 right, b: 2 left 1 right
+        assert!(plan_rucksack("abbabax", 1, &STANDARD).is_err());
+        let plan = plan_rucksack("abbaba", 1, &STANDARD).unwrap();
+        assert_eq!(plan.items_moved(), 2);
+        assert_eq!(plan.left, "bbb");
+        assert_eq!(plan.right, "aaa");
+    }
+
+    #[test]
+    fn test_plan_my_input() {
+        let input = crate::read_file_line_by_line("src/day_three/input.txt").unwrap();
+        for plan in plan_reorganization(input, &STANDARD).unwrap() {
+            let left = ItemSet::from_items(&plan.left).unwrap();
+            let right = ItemSet::from_items(&plan.right).unwrap();
+            assert!(left.intersection(right).is_empty(), "{}", plan);
+        }
+    }
+
+    #[test]
+    fn test_plan_custom_scheme() {
+        let scheme = "α 1\nβ 2\nγ 3".parse::<PriorityScheme>().unwrap();
+        let plan = plan_rucksack("αββγ", 1, &scheme).unwrap();
+        assert_eq!(plan.items_moved(), 1);
+        assert_eq!(plan.left, "αββ");
+        assert_eq!(plan.right, "γ");
+        assert!(plan_rucksack("abba", 1, &scheme).is_err());
+    }
+}
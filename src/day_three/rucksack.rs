@@ -0,0 +1,202 @@
+use std::str::FromStr;
+
+use color_eyre::{Report, Result};
+
+use super::priority::PriorityScheme;
+
+/// A set of item types stored as a bitmask where bit `n` is the item with priority `n`.
+/// The standard priorities run 1 to 52 so they all fit in a u64 with room to spare.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) struct ItemSet(u64);
+
+impl ItemSet {
+    pub(super) fn from_items(items: &str) -> Result<ItemSet> {
+        ItemSet::from_items_with(items, &PriorityScheme::Standard)
+    }
+
+    pub(super) fn from_items_with(items: &str, scheme: &PriorityScheme) -> Result<ItemSet> {
+        let mut set = ItemSet::default();
+        for item in items.chars() {
+            set.insert(item, scheme)?;
+        }
+        Ok(set)
+    }
+
+    /// The bit for `item`, anything with a priority past the end of the u64 is an error
+    fn bit(item: char, scheme: &PriorityScheme) -> Result<u64> {
+        let priority = scheme.priority_of(item)?;
+        u32::try_from(priority)
+            .ok()
+            .and_then(|priority| 1_u64.checked_shl(priority))
+            .ok_or_else(|| {
+                Report::msg(format!(
+                    "{:?} has priority {} which doesn't fit in an ItemSet",
+                    item, priority
+                ))
+            })
+    }
+
+    pub(super) fn from_priorities<I>(priorities: I) -> ItemSet
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        ItemSet(
+            priorities
+                .into_iter()
+                .fold(0, |acc, priority| acc | 1 << priority),
+        )
+    }
+
+    pub(super) fn insert(&mut self, item: char, scheme: &PriorityScheme) -> Result<()> {
+        self.0 |= ItemSet::bit(item, scheme)?;
+        Ok(())
+    }
+
+    pub(super) fn contains(&self, item: char, scheme: &PriorityScheme) -> Result<bool> {
+        Ok(self.0 & ItemSet::bit(item, scheme)? != 0)
+    }
+
+    pub(super) fn intersection(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub(super) fn union(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Priorities of the items in the set from lowest to highest
+    pub(super) fn priorities(self) -> impl Iterator<Item = usize> {
+        let mut remaining = self.0;
+        std::iter::from_fn(move || {
+            if remaining == 0 {
+                None
+            } else {
+                let priority = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(priority)
+            }
+        })
+    }
+
+    /// The items in the set ordered by priority
+    pub(super) fn items(self) -> Vec<char> {
+        self.items_with(&PriorityScheme::Standard)
+    }
+
+    pub(super) fn items_with(self, scheme: &PriorityScheme) -> Vec<char> {
+        self.priorities()
+            .map(|priority| {
+                scheme
+                    .item_of(priority)
+                    .expect("the set should be built with the same scheme")
+            })
+            .collect()
+    }
+
+    pub(super) fn priority_sum(self) -> usize {
+        self.priorities().sum()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Rucksack {
+    pub(super) left: ItemSet,
+    pub(super) right: ItemSet,
+}
+
+impl Rucksack {
+    /// Everything in the rucksack regardless of compartment
+    pub(super) fn items(&self) -> ItemSet {
+        self.left.union(self.right)
+    }
+
+    /// Item types found in both compartments
+    pub(super) fn duplicates(&self) -> ItemSet {
+        self.left.intersection(self.right)
+    }
+
+    pub(super) fn parse_with(s: &str, scheme: &PriorityScheme) -> Result<Rucksack> {
+        let (left, right) = split_compartments(s)?;
+        Ok(Rucksack {
+            left: ItemSet::from_items_with(left, scheme)?,
+            right: ItemSet::from_items_with(right, scheme)?,
+        })
+    }
+}
+
+impl FromStr for Rucksack {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Rucksack::parse_with(s, &PriorityScheme::Standard)
+    }
+}
+
+/// Splits a trimmed line in half by character so items outside of ascii still work
+pub(super) fn split_compartments(s: &str) -> Result<(&str, &str)> {
+    let s = s.trim();
+    let item_count = s.chars().count();
+    if !item_count.is_multiple_of(2) {
+        return Err(Report::msg("It said the have the same number of items in each part so I think this has be even.  At least if I am saying that the priority doesn't matter with this calculation"));
+    }
+    let middle = s
+        .char_indices()
+        .nth(item_count / 2)
+        .map(|(index, _)| index)
+        .unwrap_or(s.len());
+    Ok(s.split_at(middle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_set() {
+        let set = ItemSet::from_items("aabZ").unwrap();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains('Z', &PriorityScheme::Standard).unwrap());
+        assert!(!set.contains('z', &PriorityScheme::Standard).unwrap());
+        assert_eq!(set.priorities().collect::<Vec<_>>(), vec![1, 2, 52]);
+
+        let other = ItemSet::from_items("bc").unwrap();
+        assert_eq!(
+            set.intersection(other).priorities().collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(set.union(other).priority_sum(), 1 + 2 + 3 + 52);
+        assert!(ItemSet::default().is_empty());
+    }
+
+    #[test]
+    fn test_rucksack_duplicates() {
+        let rucksack = "vJrwpWtwJgWrhcsFMMfFFhFp".parse::<Rucksack>().unwrap();
+        assert_eq!(rucksack.duplicates(), ItemSet::from_items("p").unwrap());
+        assert!("abc".parse::<Rucksack>().is_err());
+        assert!("ab1b".parse::<Rucksack>().is_err());
+    }
+
+    #[test]
+    fn test_rucksack_custom_scheme() {
+        let scheme = "α 1\nβ 2\nγ 3".parse::<PriorityScheme>().unwrap();
+        let rucksack = Rucksack::parse_with("αββγ", &scheme).unwrap();
+        assert_eq!(rucksack.duplicates().items_with(&scheme), vec!['β']);
+        assert_eq!(rucksack.duplicates().priority_sum(), 2);
+    }
+
+    #[test]
+    fn test_item_out_of_range() {
+        assert!(ItemSet::from_items("a{").is_err());
+        assert!(ItemSet::default()
+            .contains('~', &PriorityScheme::Standard)
+            .is_err());
+    }
+}
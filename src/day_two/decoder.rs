@@ -0,0 +1,246 @@
+use std::{collections::HashMap, fmt::Display};
+
+use color_eyre::{Report, Result};
+
+use super::{
+    rules::{Outcome, RuleSet, ShapeId},
+    split_round,
+};
+
+const OUTCOMES: [Outcome; 3] = [Outcome::Loss, Outcome::Draw, Outcome::Win];
+
+/// One way of reading the second column of the strategy guide
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Decoding {
+    /// Each letter is the shape I should throw, like part one
+    Shapes(Vec<(String, ShapeId)>),
+    /// Each letter is how the round should end, like part two
+    Outcomes(Vec<(String, Outcome)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct DecodingScore {
+    pub(super) decoding: Decoding,
+    pub(super) total: usize,
+}
+
+#[derive(Debug)]
+pub(super) struct DecoderReport {
+    rules: RuleSet,
+    pub(super) scores: Vec<DecodingScore>,
+}
+
+impl DecoderReport {
+    pub(super) fn best(&self) -> Option<&DecodingScore> {
+        // max_by_key returns the last max so reverse to keep the first one found
+        self.scores.iter().rev().max_by_key(|score| score.total)
+    }
+
+    pub(super) fn worst(&self) -> Option<&DecodingScore> {
+        self.scores.iter().min_by_key(|score| score.total)
+    }
+
+    fn describe(&self, decoding: &Decoding) -> String {
+        match decoding {
+            Decoding::Shapes(mapping) => mapping
+                .iter()
+                .map(|(letter, shape)| format!("{}={}", letter, self.rules.shapes[*shape].name))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Decoding::Outcomes(mapping) => mapping
+                .iter()
+                .map(|(letter, outcome)| format!("{}={:?}", letter, outcome))
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+impl Display for DecoderReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for score in self.scores.iter() {
+            writeln!(f, "{:>8}  {}", score.total, self.describe(&score.decoding))?;
+        }
+        if let (Some(best), Some(worst)) = (self.best(), self.worst()) {
+            writeln!(
+                f,
+                "best:  {} ({})",
+                self.describe(&best.decoding),
+                best.total
+            )?;
+            writeln!(
+                f,
+                "worst: {} ({})",
+                self.describe(&worst.decoding),
+                worst.total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Scores the strategy guide under every way of reading the second column.
+///
+/// The first column is read with `rules`. The second column letters are whatever shows up in
+/// the guide, and every one to one assignment of them to shapes and to outcomes is tried.
+pub(super) fn decode_strategy_guide<I>(input: I, rules: &RuleSet) -> Result<DecoderReport>
+where
+    I: Iterator<Item = String>,
+{
+    let mut rounds = Vec::new();
+    for round in input {
+        if !round.is_empty() {
+            let (their_letter, my_letter) = split_round(&round)?;
+            rounds.push((rules.their_shape(their_letter)?, my_letter.to_string()));
+        }
+    }
+    let mut letters = rounds
+        .iter()
+        .map(|(_, letter)| letter.clone())
+        .collect::<Vec<_>>();
+    letters.sort();
+    letters.dedup();
+
+    // Only the number of times each (their shape, my letter) pair shows up matters,
+    // so count them once and score each decoding from the counts
+    let mut round_counts: HashMap<(ShapeId, usize), usize> = HashMap::new();
+    for (their_shape, letter) in rounds {
+        let letter_index = letters.binary_search(&letter).unwrap();
+        *round_counts.entry((their_shape, letter_index)).or_default() += 1;
+    }
+
+    let mut scores = Vec::new();
+    for assignment in assignments(rules.shapes.len(), letters.len()) {
+        let total = round_counts
+            .iter()
+            .map(|((their_shape, letter_index), count)| {
+                count * rules.run_match(*their_shape, assignment[*letter_index])
+            })
+            .sum();
+        scores.push(DecodingScore {
+            decoding: Decoding::Shapes(letters.iter().cloned().zip(assignment).collect()),
+            total,
+        });
+    }
+    for assignment in assignments(OUTCOMES.len(), letters.len()) {
+        let total = round_counts
+            .iter()
+            .map(|((their_shape, letter_index), count)| {
+                let outcome = OUTCOMES[assignment[*letter_index]];
+                count
+                    * rules.run_match(*their_shape, rules.shape_for_outcome(*their_shape, outcome))
+            })
+            .sum();
+        scores.push(DecodingScore {
+            decoding: Decoding::Outcomes(
+                letters
+                    .iter()
+                    .cloned()
+                    .zip(assignment.into_iter().map(|index| OUTCOMES[index]))
+                    .collect(),
+            ),
+            total,
+        });
+    }
+
+    if scores.is_empty() {
+        return Err(Report::msg(format!(
+            "no way to decode {} second column letters",
+            letters.len()
+        )));
+    }
+    Ok(DecoderReport {
+        rules: rules.clone(),
+        scores,
+    })
+}
+
+/// Every way to pick `length` distinct values from `0..choices` where order matters
+fn assignments(choices: usize, length: usize) -> Vec<Vec<usize>> {
+    if length > choices {
+        return Vec::new();
+    }
+    let mut results = Vec::new();
+    let mut current = Vec::with_capacity(length);
+    let mut used = vec![false; choices];
+    fill_assignments(&mut current, &mut used, length, &mut results);
+    results
+}
+
+fn fill_assignments(
+    current: &mut Vec<usize>,
+    used: &mut [bool],
+    length: usize,
+    results: &mut Vec<Vec<usize>>,
+) {
+    if current.len() == length {
+        results.push(current.clone());
+        return;
+    }
+    for choice in 0..used.len() {
+        if !used[choice] {
+            used[choice] = true;
+            current.push(choice);
+            fill_assignments(current, used, length, results);
+            current.pop();
+            used[choice] = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assignments() {
+        assert_eq!(assignments(3, 3).len(), 6);
+        assert_eq!(assignments(5, 3).len(), 60);
+        assert_eq!(assignments(2, 3).len(), 0);
+        assert_eq!(assignments(3, 0), vec![Vec::<usize>::new()]);
+    }
+
+    #[test]
+    fn test_decoder_example() {
+        let rules = RuleSet::classic();
+        let input = "A Y\nB X\nC Z";
+        let report =
+            decode_strategy_guide(input.split('\n').map(|x| x.to_string()), &rules).unwrap();
+        assert_eq!(report.scores.len(), 12);
+
+        let part_one = Decoding::Shapes(vec![
+            ("X".to_string(), rules.shape_id("Rock").unwrap()),
+            ("Y".to_string(), rules.shape_id("Paper").unwrap()),
+            ("Z".to_string(), rules.shape_id("Scissors").unwrap()),
+        ]);
+        let part_two = Decoding::Outcomes(vec![
+            ("X".to_string(), Outcome::Loss),
+            ("Y".to_string(), Outcome::Draw),
+            ("Z".to_string(), Outcome::Win),
+        ]);
+        let score_of = |decoding: &Decoding| {
+            report
+                .scores
+                .iter()
+                .find(|score| &score.decoding == decoding)
+                .unwrap()
+                .total
+        };
+        assert_eq!(score_of(&part_one), 15);
+        assert_eq!(score_of(&part_two), 12);
+
+        // Winning every round is the best anything can do: 6 * 3 + 2 + 1 + 3
+        assert_eq!(report.best().unwrap().total, 24);
+        // and losing every round the worst: 3 + 2 + 1
+        assert_eq!(report.worst().unwrap().total, 6);
+    }
+
+    #[test]
+    fn test_decoder_my_input() {
+        let input = crate::read_file_line_by_line("src/day_two/input.txt").unwrap();
+        let report = decode_strategy_guide(input, &RuleSet::classic()).unwrap();
+        assert!(report.scores.iter().any(|score| score.total == 15337));
+        assert!(report.scores.iter().any(|score| score.total == 11696));
+        assert!(report.best().unwrap().total >= 15337);
+    }
+}
@@ -3,7 +3,13 @@ use std::path::Path;
 
 use crate::read_file_line_by_line;
 
-use color_eyre::Result;
+use color_eyre::{Report, Result};
+
+use self::rules::RuleSet;
+
+mod decoder;
+mod rules;
+mod tournament;
 
 fn part_one_file<P>(input_path: P) -> Result<usize>
 where
@@ -19,70 +25,56 @@ where
 }
 
 fn part_one<I>(input: I) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    part_one_with_rules(input, &RuleSet::classic())
+}
+
+fn part_two<I>(input: I) -> Result<usize>
+where
+    I: Iterator<Item = String>,
+{
+    part_two_with_rules(input, &RuleSet::classic())
+}
+
+fn split_round(round: &str) -> Result<(&str, &str)> {
+    let mut split = round.split(' ');
+    match (split.next(), split.next(), split.next()) {
+        (Some(their_letter), Some(my_letter), None) => Ok((their_letter, my_letter)),
+        _ => Err(Report::msg(format!(
+            "expected two letters separated by a space in round {:?}",
+            round
+        ))),
+    }
+}
+
+fn part_one_with_rules<I>(input: I, rules: &RuleSet) -> Result<usize>
 where
     I: Iterator<Item = String>,
 {
     let mut running_sum = 0;
     for round in input {
         if !round.is_empty() {
-            let split = round.split(' ').take(2).collect::<Vec<&str>>();
-            let their_move = split[0];
-            let my_move = split[1];
-            running_sum += run_match(their_move, my_move);
+            let (their_letter, my_letter) = split_round(&round)?;
+            running_sum +=
+                rules.run_match(rules.their_shape(their_letter)?, rules.my_shape(my_letter)?);
         }
     }
     Ok(running_sum)
 }
 
-fn run_match(their_move: &str, my_move: &str) -> usize {
-    match (their_move, my_move) {
-        ("A", "X") => 4,
-        ("A", "Y") => 8,
-        ("A", "Z") => 3,
-        ("B", "X") => 1,
-        ("B", "Y") => 5,
-        ("B", "Z") => 9,
-        ("C", "X") => 7,
-        ("C", "Y") => 2,
-        ("C", "Z") => 6,
-        (_t, _m) => unreachable!(),
-    }
-}
-
-fn part_two<I>(input: I) -> Result<usize>
+fn part_two_with_rules<I>(input: I, rules: &RuleSet) -> Result<usize>
 where
     I: Iterator<Item = String>,
 {
     let mut running_sum = 0;
     for round in input {
         if !round.is_empty() {
-            let split = round.split(' ').take(2).collect::<Vec<&str>>();
-            let their_move = split[0];
-            let my_move = match split[1] {
-                // Loss
-                "X" => match their_move {
-                    "A" => "Z",
-                    "B" => "X",
-                    "C" => "Y",
-                    _ => unreachable!(),
-                },
-                // Draw
-                "Y" => match their_move {
-                    "A" => "X",
-                    "B" => "Y",
-                    "C" => "Z",
-                    _ => unreachable!(),
-                },
-                // Win
-                "Z" => match their_move {
-                    "A" => "Y",
-                    "B" => "Z",
-                    "C" => "X",
-                    _ => unreachable!(),
-                },
-                _ => unreachable!(),
-            };
-            running_sum += run_match(their_move, my_move);
+            let (their_letter, outcome_letter) = split_round(&round)?;
+            let their_shape = rules.their_shape(their_letter)?;
+            let my_shape = rules.shape_for_outcome(their_shape, rules.outcome(outcome_letter)?);
+            running_sum += rules.run_match(their_shape, my_shape);
         }
     }
     Ok(running_sum)
@@ -92,7 +84,8 @@ where
 mod tests {
     use crate::day_two::{part_two, part_two_file};
 
-    use super::{part_one, part_one_file};
+    use super::rules::RuleSet;
+    use super::{part_one, part_one_file, part_one_with_rules, part_two_with_rules};
 
     #[test]
     fn test_part_one_example() {
@@ -127,4 +120,27 @@ mod tests {
         }
         assert_eq!(11696, result.unwrap());
     }
+
+    #[test]
+    fn test_rpsls_example() {
+        let rules = RuleSet::rock_paper_scissors_lizard_spock();
+        // Lizard vs Spock: I play Spock and lose (5 + 0), Spock vs Scissors: I play Spock and win (5 + 6)
+        let input = "D Z\nC Z";
+        let result = part_one_with_rules(input.split('\n').map(|x| x.to_string()), &rules);
+        assert_eq!(16, result.unwrap());
+
+        // Rock and want a win: Paper (2) and Spock (5) both win, Spock is picked
+        let input = "A Z\nE Y";
+        let result = part_two_with_rules(input.split('\n').map(|x| x.to_string()), &rules);
+        assert_eq!((5 + 6) + (5 + 3), result.unwrap());
+    }
+
+    #[test]
+    fn test_unknown_letter() {
+        let result = part_one_with_rules(
+            "A Y\nD X".split('\n').map(|x| x.to_string()),
+            &RuleSet::classic(),
+        );
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,393 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use color_eyre::{Report, Result};
+
+// The rule files are line based so they are easy to write by hand.
+// Blank lines and lines starting with # are skipped.
+//
+//   shape <name> <points>
+//   beats <winner> <loser>
+//   points <win|draw|loss> <points>
+//   theirs <letter> <shape>      first column
+//   mine <letter> <shape>        second column when it is read as a shape (part one)
+//   outcome <letter> <win|draw|loss>   second column when it is read as an outcome (part two)
+const CLASSIC_RULES: &str = include_str!("classic_rules.txt");
+const RPSLS_RULES: &str = include_str!("rpsls_rules.txt");
+
+/// Index into [`RuleSet::shapes`]
+pub(super) type ShapeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(super) enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl FromStr for Outcome {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "loss" => Ok(Outcome::Loss),
+            "draw" => Ok(Outcome::Draw),
+            "win" => Ok(Outcome::Win),
+            other => Err(Report::msg(format!(
+                "unknown outcome {:?}, expected win, draw or loss",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Shape {
+    pub(super) name: String,
+    pub(super) points: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct RuleSet {
+    pub(super) shapes: Vec<Shape>,
+    // beats[winner][loser]
+    beats: Vec<Vec<bool>>,
+    win_points: usize,
+    draw_points: usize,
+    loss_points: usize,
+    their_letters: HashMap<String, ShapeId>,
+    my_letters: HashMap<String, ShapeId>,
+    outcome_letters: HashMap<String, Outcome>,
+}
+
+impl RuleSet {
+    pub(super) fn classic() -> RuleSet {
+        CLASSIC_RULES
+            .parse()
+            .expect("the built in classic rules should be valid")
+    }
+
+    pub(super) fn rock_paper_scissors_lizard_spock() -> RuleSet {
+        RPSLS_RULES
+            .parse()
+            .expect("the built in rock paper scissors lizard spock rules should be valid")
+    }
+
+    pub(super) fn from_file<P>(path: P) -> Result<RuleSet>
+    where
+        P: AsRef<Path>,
+    {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub(super) fn beats(&self, winner: ShapeId, loser: ShapeId) -> bool {
+        self.beats[winner][loser]
+    }
+
+    pub(super) fn outcome_of(&self, their_shape: ShapeId, my_shape: ShapeId) -> Outcome {
+        if self.beats(my_shape, their_shape) {
+            Outcome::Win
+        } else if self.beats(their_shape, my_shape) {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    pub(super) fn outcome_points(&self, outcome: Outcome) -> usize {
+        match outcome {
+            Outcome::Loss => self.loss_points,
+            Outcome::Draw => self.draw_points,
+            Outcome::Win => self.win_points,
+        }
+    }
+
+    /// My score for a single round
+    pub(super) fn run_match(&self, their_shape: ShapeId, my_shape: ShapeId) -> usize {
+        self.shapes[my_shape].points + self.outcome_points(self.outcome_of(their_shape, my_shape))
+    }
+
+    /// The shape I need to throw to get `outcome` against `their_shape`.
+    ///
+    /// With more than three shapes there can be several that work, so the one worth the most
+    /// points is picked.
+    pub(super) fn shape_for_outcome(&self, their_shape: ShapeId, outcome: Outcome) -> ShapeId {
+        (0..self.shapes.len())
+            .filter(|my_shape| self.outcome_of(their_shape, *my_shape) == outcome)
+            .max_by(|a, b| {
+                self.shapes[*a]
+                    .points
+                    .cmp(&self.shapes[*b].points)
+                    .then(b.cmp(a))
+            })
+            .expect("validation guarantees every outcome is reachable against every shape")
+    }
+
+    pub(super) fn their_shape(&self, letter: &str) -> Result<ShapeId> {
+        self.their_letters.get(letter).copied().ok_or_else(|| {
+            Report::msg(format!("{:?} is not a letter for the first column", letter))
+        })
+    }
+
+    pub(super) fn my_shape(&self, letter: &str) -> Result<ShapeId> {
+        self.my_letters.get(letter).copied().ok_or_else(|| {
+            Report::msg(format!(
+                "{:?} is not a shape letter for the second column",
+                letter
+            ))
+        })
+    }
+
+    pub(super) fn outcome(&self, letter: &str) -> Result<Outcome> {
+        self.outcome_letters.get(letter).copied().ok_or_else(|| {
+            Report::msg(format!(
+                "{:?} is not an outcome letter for the second column",
+                letter
+            ))
+        })
+    }
+
+    pub(super) fn shape_id(&self, name: &str) -> Option<ShapeId> {
+        self.shapes.iter().position(|shape| shape.name == name)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.shapes.is_empty() {
+            return Err(Report::msg("a rule set needs at least one shape"));
+        }
+        for winner in 0..self.shapes.len() {
+            if self.beats(winner, winner) {
+                return Err(Report::msg(format!(
+                    "{} can not beat itself",
+                    self.shapes[winner].name
+                )));
+            }
+            for loser in (winner + 1)..self.shapes.len() {
+                match (self.beats(winner, loser), self.beats(loser, winner)) {
+                    (true, true) => {
+                        return Err(Report::msg(format!(
+                            "{} and {} both beat each other",
+                            self.shapes[winner].name, self.shapes[loser].name
+                        )))
+                    }
+                    (false, false) => {
+                        return Err(Report::msg(format!(
+                            "no rule for {} against {}",
+                            self.shapes[winner].name, self.shapes[loser].name
+                        )))
+                    }
+                    _ => (),
+                }
+            }
+        }
+        // Part two needs to be able to win or lose against anything
+        for shape in 0..self.shapes.len() {
+            if !(0..self.shapes.len()).any(|other| self.beats(shape, other)) {
+                return Err(Report::msg(format!(
+                    "{} does not beat anything",
+                    self.shapes[shape].name
+                )));
+            }
+            if !(0..self.shapes.len()).any(|other| self.beats(other, shape)) {
+                return Err(Report::msg(format!(
+                    "nothing beats {}",
+                    self.shapes[shape].name
+                )));
+            }
+        }
+        if self.their_letters.is_empty() {
+            return Err(Report::msg("no letters given for the first column"));
+        }
+        if self.my_letters.is_empty() && self.outcome_letters.is_empty() {
+            return Err(Report::msg("no letters given for the second column"));
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut shapes: Vec<Shape> = Vec::new();
+        let mut beat_names = Vec::new();
+        let mut points: HashMap<Outcome, usize> = HashMap::new();
+        let mut their_names = Vec::new();
+        let mut my_names = Vec::new();
+        let mut outcome_letters = HashMap::new();
+
+        for (line, line_number) in s.lines().zip(1..) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let with_line = |msg: String| Report::msg(format!("line {}: {}", line_number, msg));
+            let mut split = line.split_whitespace();
+            match (split.next(), split.next(), split.next(), split.next()) {
+                (Some("shape"), Some(name), Some(shape_points), None) => {
+                    if shapes.iter().any(|shape| shape.name == name) {
+                        return Err(with_line(format!("shape {} is defined twice", name)));
+                    }
+                    shapes.push(Shape {
+                        name: name.to_string(),
+                        points: shape_points
+                            .parse()
+                            .map_err(|err| with_line(format!("{}", err)))?,
+                    });
+                }
+                (Some("beats"), Some(winner), Some(loser), None) => {
+                    beat_names.push((line_number, winner.to_string(), loser.to_string()))
+                }
+                (Some("points"), Some(outcome), Some(outcome_points), None) => {
+                    let outcome = outcome
+                        .parse::<Outcome>()
+                        .map_err(|err| with_line(format!("{}", err)))?;
+                    let outcome_points = outcome_points
+                        .parse()
+                        .map_err(|err| with_line(format!("{}", err)))?;
+                    if points.insert(outcome, outcome_points).is_some() {
+                        return Err(with_line(format!("points for {:?} given twice", outcome)));
+                    }
+                }
+                (Some("theirs"), Some(letter), Some(shape), None) => {
+                    their_names.push((line_number, letter.to_string(), shape.to_string()))
+                }
+                (Some("mine"), Some(letter), Some(shape), None) => {
+                    my_names.push((line_number, letter.to_string(), shape.to_string()))
+                }
+                (Some("outcome"), Some(letter), Some(outcome), None) => {
+                    let outcome = outcome
+                        .parse::<Outcome>()
+                        .map_err(|err| with_line(format!("{}", err)))?;
+                    if outcome_letters
+                        .insert(letter.to_string(), outcome)
+                        .is_some()
+                    {
+                        return Err(with_line(format!("outcome letter {} given twice", letter)));
+                    }
+                }
+                _ => return Err(with_line(format!("could not understand {:?}", line))),
+            }
+        }
+
+        // Shapes can be referenced before they are declared so names are resolved at the end
+        let shape_id = |line_number: usize, name: &str| {
+            shapes
+                .iter()
+                .position(|shape| shape.name == name)
+                .ok_or_else(|| Report::msg(format!("line {}: unknown shape {}", line_number, name)))
+        };
+
+        let mut beats = vec![vec![false; shapes.len()]; shapes.len()];
+        for (line_number, winner, loser) in beat_names {
+            beats[shape_id(line_number, &winner)?][shape_id(line_number, &loser)?] = true;
+        }
+
+        let mut letters_to_shapes = |names: Vec<(usize, String, String)>| {
+            let mut letters = HashMap::new();
+            for (line_number, letter, shape) in names {
+                let id = shape_id(line_number, &shape)?;
+                if letters.insert(letter.clone(), id).is_some() {
+                    return Err(Report::msg(format!(
+                        "line {}: letter {} given twice",
+                        line_number, letter
+                    )));
+                }
+            }
+            Ok(letters)
+        };
+        let their_letters = letters_to_shapes(their_names)?;
+        let my_letters = letters_to_shapes(my_names)?;
+
+        let outcome_points = |outcome: Outcome| {
+            points
+                .get(&outcome)
+                .copied()
+                .ok_or_else(|| Report::msg(format!("missing points for {:?}", outcome)))
+        };
+
+        let rule_set = RuleSet {
+            win_points: outcome_points(Outcome::Win)?,
+            draw_points: outcome_points(Outcome::Draw)?,
+            loss_points: outcome_points(Outcome::Loss)?,
+            shapes,
+            beats,
+            their_letters,
+            my_letters,
+            outcome_letters,
+        };
+        rule_set.validate()?;
+        Ok(rule_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classic_matches_original_scores() {
+        let rules = RuleSet::classic();
+        let expected = [
+            (("A", "X"), 4),
+            (("A", "Y"), 8),
+            (("A", "Z"), 3),
+            (("B", "X"), 1),
+            (("B", "Y"), 5),
+            (("B", "Z"), 9),
+            (("C", "X"), 7),
+            (("C", "Y"), 2),
+            (("C", "Z"), 6),
+        ];
+        for ((theirs, mine), score) in expected {
+            let result = rules.run_match(
+                rules.their_shape(theirs).unwrap(),
+                rules.my_shape(mine).unwrap(),
+            );
+            assert_eq!(result, score, "{} {}", theirs, mine);
+        }
+    }
+
+    #[test]
+    fn test_rpsls_outcomes() {
+        let rules = RuleSet::rock_paper_scissors_lizard_spock();
+        let spock = rules.shape_id("Spock").unwrap();
+        let lizard = rules.shape_id("Lizard").unwrap();
+        let rock = rules.shape_id("Rock").unwrap();
+
+        assert_eq!(rules.outcome_of(spock, lizard), Outcome::Win);
+        assert_eq!(rules.outcome_of(spock, rock), Outcome::Loss);
+        assert_eq!(rules.outcome_of(rock, rock), Outcome::Draw);
+        // Paper and Lizard both beat Spock, Lizard is worth more
+        assert_eq!(rules.shape_for_outcome(spock, Outcome::Win), lizard);
+    }
+
+    #[test]
+    fn test_from_file() {
+        let rules = RuleSet::from_file("src/day_two/rpsls_rules.txt").unwrap();
+        assert_eq!(rules, RuleSet::rock_paper_scissors_lizard_spock());
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let missing_rule = "
+shape Rock 1
+shape Paper 2
+beats Paper Rock
+shape Scissors 3
+points win 6
+points draw 3
+points loss 0
+theirs A Rock
+mine X Rock
+";
+        let err = missing_rule.parse::<RuleSet>().unwrap_err();
+        assert!(err.to_string().contains("no rule for"), "{}", err);
+
+        let unknown_shape = CLASSIC_RULES.replace("theirs C Scissors", "theirs C Shears");
+        let err = unknown_shape.parse::<RuleSet>().unwrap_err();
+        assert!(err.to_string().contains("unknown shape Shears"), "{}", err);
+
+        let both_beat = CLASSIC_RULES.to_string() + "beats Scissors Rock\n";
+        assert!(both_beat.parse::<RuleSet>().is_err());
+    }
+}
@@ -0,0 +1,255 @@
+use std::fmt::Display;
+
+use color_eyre::Result;
+
+use super::{
+    rules::{Outcome, RuleSet, ShapeId},
+    split_round,
+};
+
+/// One round from the strategy guide, read the part one way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Round {
+    pub(super) theirs: ShapeId,
+    pub(super) mine: ShapeId,
+}
+
+pub(super) fn parse_history<I>(input: I, rules: &RuleSet) -> Result<Vec<Round>>
+where
+    I: Iterator<Item = String>,
+{
+    let mut history = Vec::new();
+    for round in input {
+        if !round.is_empty() {
+            let (their_letter, my_letter) = split_round(&round)?;
+            history.push(Round {
+                theirs: rules.their_shape(their_letter)?,
+                mine: rules.my_shape(my_letter)?,
+            });
+        }
+    }
+    Ok(history)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct MatchStats {
+    pub(super) wins: usize,
+    pub(super) draws: usize,
+    pub(super) losses: usize,
+    pub(super) score: usize,
+    /// How often each shape was thrown, indexed by [`ShapeId`]
+    pub(super) their_shapes: Vec<usize>,
+    pub(super) my_shapes: Vec<usize>,
+}
+
+impl MatchStats {
+    fn new(rules: &RuleSet) -> MatchStats {
+        MatchStats {
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            score: 0,
+            their_shapes: vec![0; rules.shapes.len()],
+            my_shapes: vec![0; rules.shapes.len()],
+        }
+    }
+
+    fn record(&mut self, rules: &RuleSet, round: Round) {
+        match rules.outcome_of(round.theirs, round.mine) {
+            Outcome::Loss => self.losses += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Win => self.wins += 1,
+        }
+        self.score += rules.run_match(round.theirs, round.mine);
+        self.their_shapes[round.theirs] += 1;
+        self.my_shapes[round.mine] += 1;
+    }
+}
+
+pub(super) fn analyze(history: &[Round], rules: &RuleSet) -> MatchStats {
+    let mut stats = MatchStats::new(rules);
+    for round in history {
+        stats.record(rules, *round);
+    }
+    stats
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Strategy {
+    /// Play what the strategy guide says
+    Recorded,
+    /// Knows what they will throw and beats it every time
+    AlwaysWin,
+    /// Throw whatever they threw last round
+    MirrorOpponent,
+    /// Beat the shape they have thrown the most so far
+    FrequencyCounter,
+    /// Throw a random shape, the seed keeps runs repeatable
+    SeededRandom(u64),
+}
+
+impl Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Strategy::Recorded => write!(f, "recorded"),
+            Strategy::AlwaysWin => write!(f, "always win"),
+            Strategy::MirrorOpponent => write!(f, "mirror opponent"),
+            Strategy::FrequencyCounter => write!(f, "frequency counter"),
+            Strategy::SeededRandom(seed) => write!(f, "random (seed {})", seed),
+        }
+    }
+}
+
+/// Replays their recorded moves against `strategy`
+pub(super) fn simulate(history: &[Round], rules: &RuleSet, strategy: Strategy) -> MatchStats {
+    let mut stats = MatchStats::new(rules);
+    let mut random_state = match strategy {
+        // xorshift gets stuck on 0
+        Strategy::SeededRandom(seed) => seed.max(1),
+        _ => 0,
+    };
+
+    for (index, round) in history.iter().enumerate() {
+        let mine = match strategy {
+            Strategy::Recorded => round.mine,
+            Strategy::AlwaysWin => rules.shape_for_outcome(round.theirs, Outcome::Win),
+            Strategy::MirrorOpponent => match index.checked_sub(1) {
+                Some(previous) => history[previous].theirs,
+                None => 0,
+            },
+            Strategy::FrequencyCounter => {
+                // Ties go to the first shape so the results are repeatable
+                let most_common = stats
+                    .their_shapes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, count)| **count > 0)
+                    .rev()
+                    .max_by_key(|(_, count)| **count)
+                    .map(|(shape, _)| shape);
+                match most_common {
+                    Some(shape) => rules.shape_for_outcome(shape, Outcome::Win),
+                    None => 0,
+                }
+            }
+            Strategy::SeededRandom(_) => {
+                random_state ^= random_state << 13;
+                random_state ^= random_state >> 7;
+                random_state ^= random_state << 17;
+                (random_state % rules.shapes.len() as u64) as usize
+            }
+        };
+        stats.record(
+            rules,
+            Round {
+                theirs: round.theirs,
+                mine,
+            },
+        );
+    }
+    stats
+}
+
+pub(super) struct TournamentReport {
+    pub(super) results: Vec<(Strategy, MatchStats)>,
+}
+
+pub(super) fn run_tournament(
+    history: &[Round],
+    rules: &RuleSet,
+    strategies: &[Strategy],
+) -> TournamentReport {
+    TournamentReport {
+        results: strategies
+            .iter()
+            .map(|strategy| (*strategy, simulate(history, rules, *strategy)))
+            .collect(),
+    }
+}
+
+impl Display for TournamentReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>6} {:>6} {:>6} {:>8}",
+            "strategy", "wins", "draws", "losses", "score"
+        )?;
+        for (strategy, stats) in self.results.iter() {
+            writeln!(
+                f,
+                "{:<24} {:>6} {:>6} {:>6} {:>8}",
+                strategy.to_string(),
+                stats.wins,
+                stats.draws,
+                stats.losses,
+                stats.score
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STRATEGIES: [Strategy; 5] = [
+        Strategy::Recorded,
+        Strategy::AlwaysWin,
+        Strategy::MirrorOpponent,
+        Strategy::FrequencyCounter,
+        Strategy::SeededRandom(2022),
+    ];
+
+    #[test]
+    fn test_analyze_example() {
+        let rules = RuleSet::classic();
+        let history =
+            parse_history("A Y\nB X\nC Z".split('\n').map(|x| x.to_string()), &rules).unwrap();
+        let stats = analyze(&history, &rules);
+        assert_eq!((stats.wins, stats.draws, stats.losses), (1, 1, 1));
+        assert_eq!(stats.score, 15);
+        assert_eq!(stats.their_shapes, vec![1, 1, 1]);
+        assert_eq!(stats.my_shapes, vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_strategies_example() {
+        let rules = RuleSet::classic();
+        let history =
+            parse_history("A Y\nB X\nC Z".split('\n').map(|x| x.to_string()), &rules).unwrap();
+
+        assert_eq!(simulate(&history, &rules, Strategy::Recorded).score, 15);
+        assert_eq!(simulate(&history, &rules, Strategy::AlwaysWin).score, 24);
+
+        // Rock into Rock, Rock into Paper, Paper into Scissors
+        let mirror = simulate(&history, &rules, Strategy::MirrorOpponent);
+        assert_eq!((mirror.wins, mirror.draws, mirror.losses), (0, 1, 2));
+
+        let random = simulate(&history, &rules, Strategy::SeededRandom(7));
+        assert_eq!(
+            random,
+            simulate(&history, &rules, Strategy::SeededRandom(7))
+        );
+    }
+
+    #[test]
+    fn test_tournament_my_input() {
+        let rules = RuleSet::classic();
+        let input = crate::read_file_line_by_line("src/day_two/input.txt").unwrap();
+        let history = parse_history(input, &rules).unwrap();
+        let report = run_tournament(&history, &rules, &ALL_STRATEGIES);
+
+        assert_eq!(report.results[0].1.score, 15337);
+        let best = report
+            .results
+            .iter()
+            .max_by_key(|(_, stats)| stats.score)
+            .unwrap();
+        assert_eq!(best.0, Strategy::AlwaysWin);
+        for (_, stats) in report.results.iter() {
+            assert_eq!(stats.wins + stats.draws + stats.losses, history.len());
+        }
+        assert_eq!(report.to_string().lines().count(), ALL_STRATEGIES.len() + 1);
+    }
+}
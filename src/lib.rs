@@ -27,6 +27,17 @@ where
     Ok(buff_reader.lines().flatten())
 }
 
+/// A simple generator for tests that need a lot of made up input, so there's no need for a
+/// random crate. Each call gives a number below `max`.
+#[cfg(test)]
+fn test_random_numbers(seed: u64) -> impl FnMut(usize) -> usize {
+    let mut state = seed;
+    move |max: usize| {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        ((state >> 33) % max as u64) as usize
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;